@@ -1,12 +1,85 @@
-use gstd::{prelude::*, ActorId};
+use gstd::{exec, prelude::*, ActorId};
 
+use core::cmp::{max, min};
 use primitive_types::U256;
+use sha2::{Digest, Sha256};
 
 pub type TransactionId = u64;
+pub type AuctionId = u64;
+pub type Commitment = [u8; 32];
 
-/// An auction info and auction state
+/// Basis points in a whole, proceeds splits must sum up to it.
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// Most points [`PriceSchedule::points`] returns.
+pub const MAX_PRICE_POINTS: u64 = 1_000;
+
+/// Existential deposit of the network, value transfers below it can't be made.
+pub const EXISTENTIAL_DEPOSIT: u128 = 500;
+
+/// Most recipients [`CreateConfig::proceeds_splits`] can have.
+pub const MAX_PROCEEDS_SPLITS: usize = 10;
+
+/// Most finished auctions the program keeps, the oldest ones are dropped first.
+pub const MAX_HISTORY_RECORDS: usize = 100;
+
+/// Smallest deposit an offer is placed with.
+pub const MIN_OFFER_DEPOSIT: u128 = EXISTENTIAL_DEPOSIT;
+
+/// Most purchase commitments an auction accepts.
+pub const MAX_COMMITS: usize = 50;
+
+/// Auction program initialization config
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct InitConfig {
+    /// Refunds below this value are treated as dust, at most [`EXISTENTIAL_DEPOSIT`]
+    pub dust_threshold: u128,
+    /// Where the dust is credited
+    pub dust_destination: DustDestination,
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        Self {
+            dust_threshold: EXISTENTIAL_DEPOSIT,
+            dust_destination: DustDestination::Buyer,
+        }
+    }
+}
+
+/// An enum that represent the recipient of refunds below the dust threshold
+#[derive(Debug, Clone, Copy, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum DustDestination {
+    /// Dust stays on the buyer's withdrawable balance
+    #[default]
+    Buyer,
+    /// Dust is credited to the fee recipient
+    FeeRecipient(ActorId),
+}
+
+/// Auction program state, balances, commitments, bids and offers are read
+/// with [`Action::Query`]
 #[derive(Debug, Decode, Encode, TypeInfo)]
+pub struct AuctionState {
+    /// Current auction
+    pub info: AuctionInfo,
+    /// Transactions that cached on contract
+    pub transactions: BTreeMap<ActorId, Transaction<Action>>,
+    /// Current transaction id
+    pub current_tid: u64,
+    /// Refunds below this value are treated as dust
+    pub dust_threshold: u128,
+    /// Where the dust is credited
+    pub dust_destination: DustDestination,
+    /// Latest finished auctions, oldest first, at most [`MAX_HISTORY_RECORDS`]
+    pub history: Vec<AuctionRecord>,
+}
+
+/// An auction info, a lightweight summary for listings
+#[derive(Debug, Clone, Decode, Encode, TypeInfo)]
 pub struct AuctionInfo {
+    /// Id of the current auction
+    pub auction_id: AuctionId,
     /// NFT contract address
     pub nft_contract_actor_id: ActorId,
     /// NFT token id
@@ -17,25 +90,388 @@ pub struct AuctionInfo {
     pub auction_owner: ActorId,
     /// Starting price of NFT at auction
     pub starting_price: u128,
-    /// Current price of NFT
+    /// Price a buyer pays for the NFT right now, or the offer of a reverse auction
     pub current_price: u128,
     /// Price step by which the NFT price decreases
     pub discount_rate: u128,
+    /// Price below which the NFT price never decreases
+    pub min_price: u128,
+    /// Time when the auction started
+    pub started_at: u64,
     /// Time left until the end of the auction
     pub time_left: u64,
     /// Time when the auction expires
     pub expires_at: u64,
     /// Current auction status
     pub status: Status,
+    /// Recipients of the proceeds with their shares in basis points
+    pub proceeds_splits: Vec<(ActorId, u16)>,
+    /// Commit-reveal purchase settings, if enabled
+    pub commit_reveal: Option<CommitRevealConfig>,
+    /// Format of the auction
+    pub auction_type: AuctionType,
+    /// Extension of the auction on late bids, if enabled
+    pub anti_sniping: Option<AntiSnipingConfig>,
+    /// NFTs of a gradual auction put up after the current one, in order
+    pub queued_tokens: Vec<U256>,
+    /// Price for which the NFT can be bought before the current price falls to it
+    pub buy_now_price: Option<u128>,
+    /// Unit of the auction clock, all auction times are measured with it
+    pub decay_unit: DecayUnit,
+}
 
-    /// Transactions that cached on contract
-    pub transactions: BTreeMap<ActorId, Transaction<Action>>,
-    /// Current transaction id
-    pub current_tid: u64,
+impl AuctionInfo {
+    /// Price of the NFT, or the offer of a reverse auction, at `timestamp`.
+    pub fn price_at(&self, timestamp: u64) -> u128 {
+        match self.auction_type {
+            AuctionType::Reverse { budget } => self.schedule().offer_at(timestamp, budget),
+            _ => self.schedule().price_at(timestamp),
+        }
+    }
+
+    /// Points of [`PriceSchedule::points`] priced with [`AuctionInfo::price_at`],
+    /// so a reverse auction previews its rising offer.
+    pub fn price_points(&self, from: u64, until: u64, interval: u64) -> Vec<(u64, u128)> {
+        self.schedule()
+            .points(from, until, interval)
+            .into_iter()
+            .map(|(timestamp, _)| (timestamp, self.price_at(timestamp)))
+            .collect()
+    }
+
+    pub fn schedule(&self) -> PriceSchedule {
+        PriceSchedule {
+            starting_price: self.starting_price,
+            discount_rate: self.discount_rate,
+            min_price: self.min_price,
+            started_at: self.started_at,
+            decay_unit: self.decay_unit,
+            buy_now_price: self.buy_now_price,
+        }
+    }
+}
+
+/// Parameters of the descending NFT price
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriceSchedule {
+    /// Starting price of NFT at auction
+    pub starting_price: u128,
+    /// Price step by which the NFT price decreases every decay unit
+    pub discount_rate: u128,
+    /// Price below which the NFT price never decreases
+    pub min_price: u128,
+    /// Time when the price starts to decrease
+    pub started_at: u64,
+    /// Unit of the auction clock
+    pub decay_unit: DecayUnit,
+    /// Price the NFT never costs more than
+    pub buy_now_price: Option<u128>,
+}
+
+impl PriceSchedule {
+    /// Price a buyer pays for the NFT at `timestamp`: the decayed price, never
+    /// less than `min_price`, capped by `buy_now_price`.
+    pub fn price_at(&self, timestamp: u64) -> u128 {
+        let steps = self
+            .decay_unit
+            .steps(timestamp.saturating_sub(self.started_at));
+        let discount = min(
+            U256::from(self.discount_rate) * U256::from(steps),
+            U256::from(self.starting_price),
+        );
+
+        let price = max(self.starting_price - discount.as_u128(), self.min_price);
+        self.buy_now_price
+            .map_or(price, |buy_now_price| price.min(buy_now_price))
+    }
+
+    /// Offer of a reverse auction at `timestamp`, rising with `discount_rate` up to `budget`.
+    pub fn offer_at(&self, timestamp: u64, budget: u128) -> u128 {
+        let steps = self
+            .decay_unit
+            .steps(timestamp.saturating_sub(self.started_at));
+        let offer =
+            U256::from(self.starting_price) + U256::from(self.discount_rate) * U256::from(steps);
+
+        min(offer, U256::from(budget)).as_u128()
+    }
+
+    /// Schedule of the next NFT of a gradual auction after the current one
+    /// is sold for `price` at `timestamp`, the bumped price saturates at `u128::MAX`.
+    pub fn after_sale(&self, price: u128, timestamp: u64, price_bump: u16) -> PriceSchedule {
+        let bump = u32::from(BASIS_POINTS) + u32::from(price_bump);
+        let starting_price = U256::from(price) * U256::from(bump) / U256::from(BASIS_POINTS);
+        PriceSchedule {
+            starting_price: min(starting_price, U256::from(u128::MAX)).as_u128(),
+            started_at: timestamp,
+            ..*self
+        }
+    }
+
+    /// The earliest time when the price is less than or equal to `target`,
+    /// or `None` if the price never decreases that much.
+    pub fn reaches_price_at(&self, target: u128) -> Option<u64> {
+        if target >= self.starting_price
+            || matches!(self.buy_now_price, Some(price) if price <= target)
+        {
+            return Some(self.started_at);
+        }
+        if self.discount_rate == 0 || target < self.min_price {
+            return None;
+        }
+
+        let discount = self.starting_price - target;
+        let mut steps = discount / self.discount_rate;
+        if discount % self.discount_rate != 0 {
+            steps += 1;
+        }
+        let steps = u64::try_from(steps).ok()?;
+        let elapsed = match self.decay_unit {
+            DecayUnit::Second => steps.checked_mul(1000)?,
+            _ => steps,
+        };
+        self.started_at.checked_add(elapsed)
+    }
+
+    /// `(timestamp, price)` points every `interval` from `from` up to and including `until`.
+    /// The interval is widened so that there are at most [`MAX_PRICE_POINTS`] points.
+    pub fn points(&self, from: u64, until: u64, interval: u64) -> Vec<(u64, u128)> {
+        if interval == 0 || from > until {
+            return Vec::new();
+        }
+
+        let span = until - from;
+        let steps = MAX_PRICE_POINTS - 1;
+        let interval = interval.max(span / steps + u64::from(span % steps != 0));
+        let mut points: Vec<_> = (from..until)
+            .step_by(usize::try_from(interval).unwrap_or(usize::MAX))
+            .map(|timestamp| (timestamp, self.price_at(timestamp)))
+            .collect();
+        points.push((until, self.price_at(until)));
+        points
+    }
+}
+
+/// Number of seconds in a year, the limit of the auction duration
+pub const MAX_DURATION_SECONDS: u64 = 365 * 24 * 60 * 60;
+
+/// Unit of the auction clock the price decays with
+#[derive(Debug, Clone, Copy, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum DecayUnit {
+    /// The price decreases by the discount rate every whole second
+    #[default]
+    Second,
+    /// The price decreases by the discount rate every millisecond
+    Millisecond,
+    /// The price decreases by the discount rate every block,
+    /// auction times are block heights
+    Block,
+}
+
+impl DecayUnit {
+    /// Current time of the auction clock: the block height for [`DecayUnit::Block`],
+    /// the block timestamp in milliseconds otherwise.
+    pub fn now(&self) -> u64 {
+        match self {
+            DecayUnit::Block => exec::block_height().into(),
+            _ => exec::block_timestamp(),
+        }
+    }
+
+    /// Length of `duration` on the auction clock, or `None` if it's given
+    /// in the units of the other clock or doesn't fit in `u64`.
+    pub fn duration(&self, duration: &Duration) -> Option<u64> {
+        let seconds = duration
+            .hours
+            .checked_mul(60)?
+            .checked_add(duration.minutes)?
+            .checked_mul(60)?
+            .checked_add(duration.seconds)?;
+        match self {
+            DecayUnit::Block if seconds == 0 => Some(duration.blocks),
+            DecayUnit::Second | DecayUnit::Millisecond if duration.blocks == 0 => {
+                seconds.checked_mul(1000)
+            }
+            _ => None,
+        }
+    }
+
+    /// The longest auction on the clock: a year, or as many blocks as
+    /// there are seconds in a year.
+    pub fn max_duration(&self) -> u64 {
+        match self {
+            DecayUnit::Block => MAX_DURATION_SECONDS,
+            _ => MAX_DURATION_SECONDS * 1000,
+        }
+    }
+
+    /// Number of price steps during `elapsed` time of the auction clock.
+    pub fn steps(&self, elapsed: u64) -> u64 {
+        match self {
+            DecayUnit::Second => elapsed / 1000,
+            _ => elapsed,
+        }
+    }
+}
+
+/// Settings of an auction where buyers commit to a purchase before revealing it
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct CommitRevealConfig {
+    /// Minimal value attached to [`Action::CommitPurchase`], at least
+    /// [`MIN_OFFER_DEPOSIT`]
+    pub deposit: u128,
+    /// Time after a commit within which it must be revealed
+    pub reveal_window: Duration,
+}
+
+/// A purchase commitment of the current auction
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct PurchaseCommit {
+    /// Actor that made the commitment
+    pub buyer: ActorId,
+    /// Hash made with [`commitment`]
+    pub commitment: Commitment,
+    /// Price of the NFT at the time of the commit
+    pub price: u128,
+    /// Value attached to the commit and the reveal
+    pub paid: u128,
+    /// Time when the commitment must be revealed by
+    pub reveal_deadline: u64,
+    /// Whether the commitment was revealed
+    pub revealed: bool,
+}
+
+/// Commitment for [`Action::CommitPurchase`] that `buyer` reveals with `salt`.
+pub fn commitment(buyer: &ActorId, salt: &[u8; 32]) -> Commitment {
+    let mut hasher = Sha256::new();
+    hasher.update(buyer.as_ref());
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// An enum that represent the format of an auction
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum AuctionType {
+    /// The price decreases until someone buys the NFT
+    #[default]
+    Dutch,
+    /// Ascending bids starting from the starting price, each one at least
+    /// `min_increment` above the previous one
+    English { min_increment: u128 },
+    /// Sealed bids revealed within `reveal_window` after the bidding ends,
+    /// the highest bidder pays the second highest bid
+    SealedBid { reveal_window: Duration },
+    /// The offer for a service rises until a provider accepts it, the escrowed
+    /// `budget` caps the offer. Started with [`Action::CreateReverse`], the
+    /// creator is reported as the seller
+    Reverse { budget: u128 },
+    /// Sequential Dutch sales of `token_ids` after `token_id`. The price of
+    /// the next NFT starts from the previous sale price raised by `price_bump`
+    /// basis points. The NFTs stay with the seller, approved to the program,
+    /// and the proceeds are paid out on every sale
+    Gradual {
+        token_ids: Vec<U256>,
+        price_bump: u16,
+    },
+}
+
+/// Reverse auction config, the value attached to [`Action::CreateReverse`] is the budget
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct ReverseConfig {
+    /// Offer at the start of the auction
+    pub starting_price: u128,
+    /// Price step by which the offer increases every decay unit
+    pub increase_rate: u128,
+    /// Auction duration
+    pub duration: Duration,
+    /// Unit of the auction clock
+    pub decay_unit: DecayUnit,
+}
+
+/// Settings of the auction extension on late bids
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct AntiSnipingConfig {
+    /// Bids placed within this time before the end extend the auction
+    pub threshold: Duration,
+    /// Time the auction is extended by
+    pub extension: Duration,
+}
+
+/// A bid of an English or a sealed-bid auction
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct Bid {
+    /// Actor that placed the bid
+    pub bidder: ActorId,
+    /// Bid amount, zero until a sealed bid is revealed
+    pub amount: u128,
+    /// Value attached to the bid
+    pub deposit: u128,
+    /// Hash of a sealed bid that isn't revealed yet, made with [`bid_commitment`]
+    pub commitment: Option<Commitment>,
+}
+
+/// Commitment for [`Action::SealBid`] that `bidder` reveals with `amount` and `salt`.
+pub fn bid_commitment(bidder: &ActorId, amount: u128, salt: &[u8; 32]) -> Commitment {
+    let mut hasher = Sha256::new();
+    hasher.update(bidder.as_ref());
+    hasher.update(amount.to_le_bytes());
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// An escrowed offer to buy the NFT for `price`
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct Offer {
+    /// Highest price the offerer pays
+    pub price: u128,
+    /// Value attached to the offer
+    pub deposit: u128,
+    /// Time when the offer was placed
+    pub placed_at: u64,
+    /// Time when the offer expires, offers placed with [`Action::PlaceOffer`]
+    /// last until the auction ends
+    pub expires_at: Option<u64>,
+}
+
+impl Offer {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| now >= expires_at)
+    }
+}
+
+/// A finished auction kept in the program history
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct AuctionRecord {
+    /// The auctioned NFT
+    pub lot: Lot,
+    /// Buyer of the NFT, if it was sold
+    pub buyer: Option<ActorId>,
+    /// Time when the auction started, on the `decay_unit` clock
+    pub started_at: u64,
+    /// Time when the auction ended, on the `decay_unit` clock
+    pub ended_at: u64,
+    /// How the auction ended
+    pub outcome: Outcome,
+    /// Clock the auction ran on
+    pub decay_unit: DecayUnit,
+}
+
+/// An enum that represent how an auction ended
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum Outcome {
+    /// The NFT was bought for `price`
+    Sold { price: u128 },
+    /// No one bought the NFT and it was relisted
+    Expired,
+    /// Auction stopped by auction owner
+    Stopped,
+    /// No one bought the NFT and the seller took it back
+    Reclaimed,
 }
 
 /// An enum that represent current auction status
-#[derive(Debug, Decode, Default, Encode, TypeInfo, Clone)]
+#[derive(Debug, Decode, Default, Encode, TypeInfo, Clone, PartialEq, Eq)]
 pub enum Status {
     #[default]
     None,
@@ -49,9 +485,25 @@ pub enum Status {
     Expired,
     /// Auction stopped by auction owner
     Stopped,
+    /// Auction expired and the seller took the NFT back
+    Reclaimed,
+}
+
+impl Status {
+    /// Status of an auction that expires at `expires_at` as seen at `now`.
+    ///
+    /// The program, its `state()` and the metawasm functions all derive the
+    /// status with it, so they never disagree.
+    pub fn effective(&self, expires_at: u64, now: u64) -> Status {
+        if matches!(self, Status::IsRunning) && now >= expires_at {
+            Status::Expired
+        } else {
+            self.clone()
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo)]
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
 pub struct Transaction<T: Clone> {
     pub id: TransactionId,
     pub action: T,
@@ -64,48 +516,348 @@ pub struct Transaction<T: Clone> {
 pub enum Action {
     /// Creates auction
     Create(CreateConfig),
-    /// Buy current NFT
-    Buy,
+    /// Buy current NFT if its price doesn't exceed `max_price`. An offer that
+    /// covers the price is filled instead and the buy fails with [`Error::OfferFilled`]
+    Buy { max_price: u128 },
     /// Stop Auction
     ForceStop,
     /// Reward gas to NFT seller
     Reward,
+    /// Extend the running auction by the given duration
+    ExtendAuction { duration: Duration },
+    /// Restart an expired auction with new pricing, the NFT stays on the program
+    Relist(CreateConfig),
+    /// Return the NFT of an expired auction to its seller
+    ReclaimNft,
+    /// Withdraw refunds and proceeds accumulated for the sender
+    Withdraw,
+    /// Buy current NFT and transfer it to `recipient`, refunds go to the sender
+    BuyFor { recipient: ActorId, max_price: u128 },
+    /// Commit to buy the NFT at its current price, the attached value is the deposit.
+    /// At most [`MAX_COMMITS`] commitments are accepted per auction
+    CommitPurchase { commitment: Commitment },
+    /// Reveal the sender's commitment, the attached value adds to the deposit
+    RevealPurchase { salt: [u8; 32] },
+    /// Bid the attached value in an English auction
+    Bid,
+    /// Place a sealed bid, the attached value must cover the bid
+    SealBid { commitment: Commitment },
+    /// Reveal the sender's sealed bid after the bidding ends
+    RevealBid { amount: u128, salt: [u8; 32] },
+    /// Sell the NFT to the winning bid once the bidding is over
+    Settle,
+    /// Start a reverse auction escrowing the attached value as its budget
+    CreateReverse(ReverseConfig),
+    /// Accept the current offer of the reverse auction and receive it
+    Accept,
+    /// Return the budget of an expired reverse auction to its creator
+    ReclaimBudget,
+    /// Offer to buy the NFT once its price falls to `price`, the attached
+    /// value must cover it and [`MIN_OFFER_DEPOSIT`]. Replaces the previous
+    /// offer of the sender
+    PlaceOffer { price: u128 },
+    /// Sell the NFT to the best offer at or above the current price, a buy
+    /// fills it first as well
+    FillOffer,
+    /// Offer the attached value, at least [`MIN_OFFER_DEPOSIT`], for the NFT of an
+    /// expired or stopped auction, the offer expires after `duration`. Replaces
+    /// the previous offer of the sender
+    MakeOffer { duration: Duration },
+    /// Sell the NFT to `offerer` for its offer, made by the seller while they still own the NFT
+    AcceptOffer { offerer: ActorId },
+    /// Refund the offer of `offerer`, made by the seller
+    RejectOffer { offerer: ActorId },
+    /// Return the sender's offer
+    WithdrawOffer,
+    /// Read data kept out of the program state, replied with [`Event::StateReply`]
+    Query(StateQuery),
+}
+
+/// Data kept out of the program state to keep it small, see [`Action::Query`]
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum StateQuery {
+    /// Refunds and proceeds the actor can withdraw
+    Balance(ActorId),
+    /// Purchase commitments of the current auction, earliest first
+    Commits,
+    /// Bids of the current auction, earliest first
+    Bids,
+    /// Standing offers for the current NFT
+    Offers,
+}
+
+/// Finished auctions read from [`AuctionState::history`]
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum HistoryFilter {
+    #[default]
+    All,
+    /// Auctions of the seller
+    Seller(ActorId),
+    /// Auctions of NFTs from the collection
+    Collection(ActorId),
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, record: &AuctionRecord) -> bool {
+        match self {
+            HistoryFilter::All => true,
+            HistoryFilter::Seller(seller) => record.lot.seller == *seller,
+            HistoryFilter::Collection(nft_contract_id) => {
+                record.lot.nft_contract_id == *nft_contract_id
+            }
+        }
+    }
+
+    /// `page_size` records of `history` matching the filter starting from
+    /// `page * page_size`, oldest first.
+    pub fn page(&self, history: &[AuctionRecord], page: u32, page_size: u32) -> Vec<AuctionRecord> {
+        history
+            .iter()
+            .filter(|record| self.matches(record))
+            .skip((page as usize).saturating_mul(page_size as usize))
+            .take(page_size as usize)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Reply to a [`StateQuery`]
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum StateReply {
+    Balance(u128),
+    Commits(Vec<PurchaseCommit>),
+    Bids(Vec<Bid>),
+    Offers(Vec<(ActorId, Offer)>),
+}
+
+/// The auctioned NFT an [`Event`] refers to
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub struct Lot {
+    /// Id of the auction, increased on every (re)listing
+    pub auction_id: AuctionId,
+    /// NFT contract address
+    pub nft_contract_id: ActorId,
+    /// NFT token id
+    pub token_id: U256,
+    /// NFT seller
+    pub seller: ActorId,
 }
 
 /// An enum that contains a result of processed [`Action`].
-#[derive(Debug, Encode, Decode, TypeInfo)]
+#[derive(Debug, Encode, Decode, TypeInfo, PartialEq, Eq)]
 pub enum Event {
     AuctionStarted {
-        /// Owner of auction NFT
-        token_owner: ActorId,
+        /// The auctioned NFT
+        lot: Lot,
         /// Started price of NFT
         price: u128,
-        /// NFT token id
-        token_id: U256,
+        /// Time when the auction expires
+        expires_at: u64,
+        /// Block timestamp of the event
+        timestamp: u64,
     },
     Bought {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Buyer of the NFT
+        buyer: ActorId,
+        /// Actor the NFT was transferred to
+        recipient: ActorId,
         /// Price for which the NFT were bought
         price: u128,
+        /// Part of the overpayment credited to the dust destination
+        dust: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    AuctionStopped {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    Rewarded {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Buyer of the NFT
+        buyer: ActorId,
+        /// Reward that owner received
+        price: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    AuctionExtended {
+        /// The auctioned NFT
+        lot: Lot,
+        /// New time when the auction expires
+        expires_at: u64,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    NftReclaimed {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    Withdrawn {
+        /// Actor the value was sent to
+        actor: ActorId,
+        /// Value sent to the withdrawer
+        amount: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    PurchaseCommitted {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that made the commitment
+        buyer: ActorId,
+        /// Price the NFT is bought for if the commit wins
+        price: u128,
+        /// Time when the commitment must be revealed by
+        reveal_deadline: u64,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    /// The commitment is revealed but waits for the earlier ones
+    PurchaseRevealed {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that revealed the commitment
+        buyer: ActorId,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    BidPlaced {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that placed the bid
+        bidder: ActorId,
+        /// Bid amount
+        amount: u128,
+        /// Time when the auction expires, extended by late bids
+        expires_at: u64,
+        /// Whether this bid extended the auction
+        extended: bool,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    BidSealed {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that placed the bid
+        bidder: ActorId,
+        /// Time when the auction expires, extended by late bids
+        expires_at: u64,
+        /// Whether this bid extended the auction
+        extended: bool,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    BidRevealed {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that placed the bid
+        bidder: ActorId,
+        /// Bid amount
+        amount: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    Accepted {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that accepted the offer
+        provider: ActorId,
+        /// Offer paid to the provider
+        price: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    BudgetReclaimed {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Budget returned to the creator
+        amount: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    OfferPlaced {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that placed the offer
+        offerer: ActorId,
+        /// Highest price the offerer pays
+        price: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    OfferRejected {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor whose offer was rejected
+        offerer: ActorId,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    OfferWithdrawn {
+        /// The auctioned NFT
+        lot: Lot,
+        /// Actor that withdrew the offer
+        offerer: ActorId,
+        /// Value returned to the offerer
+        amount: u128,
+        /// Block timestamp of the event
+        timestamp: u64,
+    },
+    StateReply(StateReply),
+}
+
+/// Event layout of releases up to 0.1.5.
+#[derive(Debug, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum EventV1 {
+    AuctionStarted {
+        token_owner: ActorId,
+        price: u128,
+        token_id: U256,
+    },
+    Bought {
+        price: u128,
     },
     AuctionStopped {
         token_owner: ActorId,
         token_id: U256,
     },
     Rewarded {
-        /// Reward that owner received
         price: u128,
     },
 }
 
-#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+/// An [`Event`] tagged with its layout version, this is what the program replies with.
+#[derive(Debug, Encode, Decode, TypeInfo, PartialEq, Eq)]
+pub enum VersionedEvent {
+    V1(EventV1),
+    V2(Event),
+}
+
+impl From<Event> for VersionedEvent {
+    fn from(event: Event) -> Self {
+        Self::V2(event)
+    }
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
 pub struct Duration {
     pub hours: u64,
     pub minutes: u64,
     pub seconds: u64,
+    /// Number of blocks, the only unit of [`DecayUnit::Block`] auctions
+    pub blocks: u64,
 }
 
 /// Dutch Auction config
-#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
 pub struct CreateConfig {
     /// Address of NFT contract
     pub nft_contract_actor_id: ActorId,
@@ -113,14 +865,32 @@ pub struct CreateConfig {
     pub token_id: U256,
     /// Starting price
     pub starting_price: u128,
-    /// Price step by which the NFT price decreases
+    /// Price step by which the NFT price decreases, zero only in the fixed price mode
     pub discount_rate: u128,
+    /// Sell the NFT at the starting price without decay
+    pub fixed_price: bool,
+    /// Price below which the NFT price never decreases
+    pub min_price: u128,
     /// Auction duration
     pub duration: Duration,
+    /// Distinct non-zero recipients of the proceeds with their shares in basis
+    /// points, at most [`MAX_PROCEEDS_SPLITS`]; the seller receives everything if empty
+    pub proceeds_splits: Vec<(ActorId, u16)>,
+    /// Require buyers to commit and reveal their purchases instead of `Buy`
+    pub commit_reveal: Option<CommitRevealConfig>,
+    /// Format of the auction
+    pub auction_type: AuctionType,
+    /// Extend the auction on bids placed right before its end
+    pub anti_sniping: Option<AntiSnipingConfig>,
+    /// Price for which the NFT can be bought before the current price falls
+    /// to it, non-zero and between `min_price` and the starting price
+    pub buy_now_price: Option<u128>,
+    /// Unit of the auction clock the price decays with
+    pub decay_unit: DecayUnit,
 }
 
 /// An enum that contains a error of processed [`Action`].
-#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
 pub enum Error {
     PreviousTxMustBeCompleted,
     SendingError,
@@ -130,6 +900,8 @@ pub enum Error {
     NftNotApproved,
     NotRewarded,
     WrongReply,
+    /// Deprecated: rewards that can't be sent are credited to the seller's
+    /// withdrawable balance instead
     RewardSendFailed,
     NotOwner,
     AlreadyRunning,
@@ -139,4 +911,36 @@ pub enum Error {
     Expired,
     WrongState,
     IncorrectRewarder,
+    NotSeller,
+    WrongToken,
+    NothingToWithdraw,
+    InvalidProceedsSplits,
+    InvalidRecipient,
+    MaxPriceExceeded,
+    InvalidMinPrice,
+    CommitRevealRequired,
+    NotCommitReveal,
+    AlreadyCommitted,
+    NoCommitment,
+    InvalidReveal,
+    RevealWindowClosed,
+    WrongAuctionType,
+    BidTooLow,
+    BidsPending,
+    NoBids,
+    NoFillableOffer,
+    NoOffer,
+    OfferExpired,
+    InvalidBuyNowPrice,
+    InvalidDuration,
+    PriceOverflow,
+    ZeroDuration,
+    DurationTooLong,
+    ZeroStartingPrice,
+    ZeroDiscountRate,
+    FixedPriceWithDiscount,
+    InvalidNftContract,
+    InvalidCommitDeposit,
+    TooManyCommits,
+    OfferFilled,
 }
@@ -1,14 +1,14 @@
-use gmeta::{InOut, Metadata};
+use gmeta::{In, InOut, Metadata};
 
-use crate::auction::{Action, AuctionInfo, Error, Event};
+use crate::auction::{Action, AuctionState, Error, InitConfig, VersionedEvent};
 
 pub struct AuctionMetadata;
 
 impl Metadata for AuctionMetadata {
-    type Init = ();
-    type Handle = InOut<Action, Result<Event, Error>>;
+    type Init = In<InitConfig>;
+    type Handle = InOut<Action, Result<VersionedEvent, Error>>;
     type Others = ();
     type Reply = ();
     type Signal = ();
-    type State = AuctionInfo;
+    type State = AuctionState;
 }
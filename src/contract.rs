@@ -1,8 +1,12 @@
 use auction_io::auction::{
-    Action, AuctionInfo, CreateConfig, Error, Event, Status, Transaction, TransactionId,
+    bid_commitment, commitment, Action, AntiSnipingConfig, AuctionId, AuctionInfo, AuctionRecord,
+    AuctionState, AuctionType, Bid, CommitRevealConfig, Commitment, CreateConfig, DecayUnit,
+    Duration, DustDestination, Error, Event, InitConfig, Lot, Offer, Outcome, PriceSchedule,
+    PurchaseCommit, ReverseConfig, StateQuery, StateReply, Status, Transaction, TransactionId,
+    VersionedEvent, BASIS_POINTS, EXISTENTIAL_DEPOSIT, MAX_COMMITS, MAX_HISTORY_RECORDS,
+    MAX_PROCEEDS_SPLITS, MIN_OFFER_DEPOSIT,
 };
 use auction_io::io::AuctionMetadata;
-use core::cmp::min;
 use gmeta::Metadata;
 use gstd::ActorId;
 use gstd::{errors::Result as GstdResult, exec, msg, prelude::*, MessageId};
@@ -21,102 +25,759 @@ pub struct Nft {
 #[derive(Debug, Clone, Default)]
 pub struct Auction {
     pub owner: ActorId,
+    pub auction_id: AuctionId,
     pub nft: Nft,
+    pub buyer: ActorId,
     pub starting_price: u128,
     pub discount_rate: u128,
+    pub min_price: u128,
     pub status: Status,
     pub started_at: u64,
     pub expires_at: u64,
 
     pub transactions: BTreeMap<ActorId, Transaction<Action>>,
     pub current_tid: TransactionId,
+
+    pub balances: BTreeMap<ActorId, u128>,
+    pub dust_threshold: u128,
+    pub dust_destination: DustDestination,
+
+    pub history: Vec<AuctionRecord>,
+
+    pub proceeds_splits: Vec<(ActorId, u16)>,
+
+    pub commit_reveal: Option<CommitRevealConfig>,
+    pub commits: Vec<PurchaseCommit>,
+
+    pub auction_type: AuctionType,
+    pub bids: Vec<Bid>,
+    pub anti_sniping: Option<AntiSnipingConfig>,
+    pub queue: Vec<U256>,
+
+    pub offers: BTreeMap<ActorId, Offer>,
+
+    pub buy_now_price: Option<u128>,
+
+    pub decay_unit: DecayUnit,
 }
 
 impl Auction {
-    pub async fn buy(&mut self, transaction_id: TransactionId) -> Result<(Event, u128), Error> {
+    /// Current time of the auction clock.
+    fn now(&self) -> u64 {
+        self.decay_unit.now()
+    }
+
+    /// Length of an already validated duration on the auction clock.
+    fn clock(&self, duration: &Duration) -> u64 {
+        self.decay_unit.duration(duration).unwrap_or_default()
+    }
+
+    pub async fn buy(
+        &mut self,
+        transaction_id: TransactionId,
+        recipient: ActorId,
+        max_price: u128,
+    ) -> Result<Event, Error> {
+        if recipient.is_zero() {
+            return Err(Error::InvalidRecipient);
+        }
         if !matches!(self.status, Status::IsRunning) {
             return Err(Error::AlreadyStopped);
         }
 
-        if exec::block_timestamp() >= self.expires_at {
+        if self.now() >= self.expires_at {
             return Err(Error::Expired);
         }
 
+        if !matches!(
+            self.auction_type,
+            AuctionType::Dutch | AuctionType::Gradual { .. }
+        ) {
+            return Err(Error::WrongAuctionType);
+        }
+        if self.commit_reveal.is_some() {
+            return Err(Error::CommitRevealRequired);
+        }
+        // Offers the price has fallen to take priority over the buyer
+        if self.fillable_offer().is_some() {
+            self.fill_offer(transaction_id).await?;
+            return Err(Error::OfferFilled);
+        }
+
         let price = self.token_price();
+        if price > max_price {
+            return Err(Error::MaxPriceExceeded);
+        }
         let value = msg::value();
         if value < price {
             return Err(Error::InsufficientMoney);
         }
 
-        self.status = Status::Purchased { price };
+        self.purchase(transaction_id, msg::source(), recipient, price, value)
+            .await
+    }
+
+    /// Pays out the sale of a gradual auction and puts up its next NFT.
+    fn next_sale(&mut self, price: u128, price_bump: u16) {
+        self.pay_proceeds(price);
+        self.status = Status::Rewarded { price };
+        if self.queue.is_empty() {
+            return;
+        }
+
+        let now = self.now();
+        let schedule = self.schedule().after_sale(price, now, price_bump);
+        self.nft.token_id = self.queue.remove(0);
+        self.auction_id = self.auction_id.wrapping_add(1);
+        self.status = Status::IsRunning;
+        self.starting_price = schedule.starting_price;
+        self.started_at = schedule.started_at;
+    }
 
-        let refund = value - price;
-        let refund = if refund < 500 { 0 } else { refund };
+    /// Sells the NFT to `buyer` for `price` and transfers it to `recipient`,
+    /// crediting the overpayment.
+    async fn purchase(
+        &mut self,
+        transaction_id: TransactionId,
+        buyer: ActorId,
+        recipient: ActorId,
+        price: u128,
+        paid: u128,
+    ) -> Result<Event, Error> {
+        let status = mem::replace(&mut self.status, Status::Purchased { price });
+        let previous_buyer = mem::replace(&mut self.buyer, buyer);
 
-        let reply = match msg::send_for_reply(
+        let transferred = match msg::send_for_reply(
             self.nft.contract_id,
             NFTAction::Transfer {
-                to: msg::source(),
+                to: recipient,
                 token_id: self.nft.token_id,
                 transaction_id,
             },
             0,
             0,
         ) {
-            Ok(reply) => reply,
-            Err(_e) => {
-                return Err(Error::NftTransferFailed);
-            }
+            Ok(reply) => reply.await.is_ok(),
+            Err(_e) => false,
         };
+        if !transferred {
+            // Nothing was sold, so the seller must not be rewarded
+            self.status = status;
+            self.buyer = previous_buyer;
+            return Err(Error::NftTransferFailed);
+        }
+
+        self.record(Outcome::Sold { price });
 
-        match reply.await {
-            Ok(_reply) => {}
-            Err(_e) => {
-                return Err(Error::NftTransferFailed);
+        let refund = paid - price;
+        let dust = if refund < self.dust_threshold {
+            refund
+        } else {
+            0
+        };
+        let refund_to = match self.dust_destination {
+            DustDestination::FeeRecipient(recipient) if dust > 0 => recipient,
+            _ => buyer,
+        };
+        self.credit(refund_to, refund);
+
+        let bought = Event::Bought {
+            lot: self.lot(),
+            buyer: self.buyer,
+            recipient,
+            price,
+            dust,
+            timestamp: exec::block_timestamp(),
+        };
+        if let AuctionType::Gradual { price_bump, .. } = self.auction_type {
+            self.next_sale(price, price_bump);
+        }
+        Ok(bought)
+    }
+
+    pub fn place_offer(&mut self, price: u128) -> Result<Event, Error> {
+        if !matches!(self.status, Status::IsRunning) {
+            return Err(Error::AlreadyStopped);
+        }
+        if !matches!(
+            self.auction_type,
+            AuctionType::Dutch | AuctionType::Gradual { .. }
+        ) {
+            return Err(Error::WrongAuctionType);
+        }
+        if self.commit_reveal.is_some() {
+            return Err(Error::CommitRevealRequired);
+        }
+        let deposit = msg::value();
+        if deposit < price.max(MIN_OFFER_DEPOSIT) {
+            return Err(Error::InsufficientMoney);
+        }
+
+        let offerer = msg::source();
+        let offer = Offer {
+            price,
+            deposit,
+            placed_at: self.now(),
+            expires_at: None,
+        };
+        if let Some(previous) = self.offers.insert(offerer, offer) {
+            self.credit(offerer, previous.deposit);
+        }
+
+        Ok(Event::OfferPlaced {
+            lot: self.lot(),
+            offerer,
+            price,
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    /// The highest offer at or above the current price, the earliest one on ties.
+    pub fn fillable_offer(&self) -> Option<ActorId> {
+        if !matches!(self.status, Status::IsRunning) {
+            return None;
+        }
+
+        let price = self.token_price();
+        let now = self.now();
+        self.offers
+            .iter()
+            .filter(|(_, offer)| offer.price >= price && !offer.is_expired(now))
+            .max_by(|(_, a), (_, b)| a.price.cmp(&b.price).then(b.placed_at.cmp(&a.placed_at)))
+            .map(|(offerer, _)| *offerer)
+    }
+
+    /// Sells the NFT at the current price to the best offer that covers it.
+    pub async fn fill_offer(&mut self, transaction_id: TransactionId) -> Result<Event, Error> {
+        let offerer = self.fillable_offer().ok_or(Error::NoFillableOffer)?;
+        let offer = self
+            .offers
+            .remove(&offerer)
+            .expect("The fillable offer exists");
+
+        let price = self.token_price();
+        let result = self
+            .purchase(transaction_id, offerer, offerer, price, offer.deposit)
+            .await;
+        if result.is_err() {
+            self.credit(offerer, offer.deposit);
+        }
+        result
+    }
+
+    pub fn make_offer(&mut self, duration: &Duration) -> Result<Event, Error> {
+        if !matches!(
+            self.status,
+            Status::Expired | Status::Stopped | Status::Reclaimed
+        ) {
+            return Err(Error::WrongState);
+        }
+        if matches!(self.auction_type, AuctionType::Reverse { .. }) {
+            return Err(Error::WrongAuctionType);
+        }
+
+        let price = msg::value();
+        if price < MIN_OFFER_DEPOSIT {
+            return Err(Error::InsufficientMoney);
+        }
+
+        let now = self.now();
+        let expires_at = self
+            .decay_unit
+            .duration(duration)
+            .and_then(|duration| now.checked_add(duration))
+            .ok_or(Error::InvalidDuration)?;
+        let offerer = msg::source();
+        let offer = Offer {
+            price,
+            deposit: price,
+            placed_at: now,
+            expires_at: Some(expires_at),
+        };
+        if let Some(previous) = self.offers.insert(offerer, offer) {
+            self.credit(offerer, previous.deposit);
+        }
+
+        Ok(Event::OfferPlaced {
+            lot: self.lot(),
+            offerer,
+            price,
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    /// Sells the NFT of an expired or stopped auction to `offerer`. The NFT
+    /// that the program doesn't hold must be approved to it again.
+    pub async fn accept_offer(
+        &mut self,
+        transaction_id: TransactionId,
+        offerer: ActorId,
+    ) -> Result<Event, Error> {
+        if !matches!(
+            self.status,
+            Status::Expired | Status::Stopped | Status::Reclaimed
+        ) {
+            return Err(Error::WrongState);
+        }
+        if msg::source().ne(&self.nft.owner) {
+            return Err(Error::NotSeller);
+        }
+        if self.has_pending_bids() {
+            return Err(Error::BidsPending);
+        }
+        let offer = self.offers.get(&offerer).ok_or(Error::NoOffer)?;
+        if offer.is_expired(self.now()) {
+            return Err(Error::OfferExpired);
+        }
+
+        let held = matches!(self.status, Status::Expired)
+            && !matches!(self.auction_type, AuctionType::Gradual { .. });
+        if !held {
+            // The seller may have handed the NFT on after reclaiming it
+            let owner = Self::get_token_owner(self.nft.contract_id, self.nft.token_id).await?;
+            if owner != self.nft.owner {
+                return Err(Error::NotSeller);
             }
+            self.validate_nft_approve(self.nft.contract_id, self.nft.token_id)
+                .await?;
         }
 
-        Ok((Event::Bought { price }, refund))
+        let offer = self.offers.remove(&offerer).expect("The offer exists");
+        let result = self
+            .purchase(transaction_id, offerer, offerer, offer.price, offer.deposit)
+            .await;
+        if result.is_err() {
+            self.credit(offerer, offer.deposit);
+        }
+        result
     }
 
-    pub fn token_price(&self) -> u128 {
-        // time_elapsed is in seconds
-        let time_elapsed = exec::block_timestamp().saturating_sub(self.started_at) / 1000;
-        let discount = min(
-            self.discount_rate * (time_elapsed as u128),
-            self.starting_price,
-        );
+    pub fn reject_offer(&mut self, offerer: ActorId) -> Result<Event, Error> {
+        if msg::source().ne(&self.nft.owner) {
+            return Err(Error::NotSeller);
+        }
+        let offer = self.offers.remove(&offerer).ok_or(Error::NoOffer)?;
+        self.credit(offerer, offer.deposit);
 
-        self.starting_price - discount
+        Ok(Event::OfferRejected {
+            lot: self.lot(),
+            offerer,
+            timestamp: exec::block_timestamp(),
+        })
     }
 
-    pub async fn renew_contract(
+    pub fn withdraw_offer(&mut self) -> Result<(Event, u128), Error> {
+        let offerer = msg::source();
+        let offer = self.offers.remove(&offerer).ok_or(Error::NoOffer)?;
+
+        Ok((
+            Event::OfferWithdrawn {
+                lot: self.lot(),
+                offerer,
+                amount: offer.deposit,
+                timestamp: exec::block_timestamp(),
+            },
+            offer.deposit,
+        ))
+    }
+
+    /// Refunds the offers matching `release`.
+    fn release_offers(&mut self, release: impl Fn(&Offer) -> bool) {
+        let (released, kept): (BTreeMap<_, _>, _) = mem::take(&mut self.offers)
+            .into_iter()
+            .partition(|(_, offer)| release(offer));
+        self.offers = kept;
+        for (offerer, offer) in released {
+            self.credit(offerer, offer.deposit);
+        }
+    }
+
+    pub fn commit_purchase(&mut self, commitment: Commitment) -> Result<Event, Error> {
+        if !matches!(self.status, Status::IsRunning) {
+            return Err(Error::AlreadyStopped);
+        }
+        let Some(config) = &self.commit_reveal else {
+            return Err(Error::NotCommitReveal);
+        };
+        if !matches!(self.auction_type, AuctionType::Dutch) {
+            return Err(Error::WrongAuctionType);
+        }
+
+        let now = self.now();
+        let reveal_deadline = now.saturating_add(self.clock(&config.reveal_window));
+        if reveal_deadline > self.expires_at {
+            return Err(Error::Expired);
+        }
+        let paid = msg::value();
+        if paid < config.deposit {
+            return Err(Error::InsufficientMoney);
+        }
+        let buyer = msg::source();
+        if self.commits.iter().any(|commit| commit.buyer == buyer) {
+            return Err(Error::AlreadyCommitted);
+        }
+        if self.commits.len() >= MAX_COMMITS {
+            return Err(Error::TooManyCommits);
+        }
+
+        let price = self.token_price();
+        self.commits.push(PurchaseCommit {
+            buyer,
+            commitment,
+            price,
+            paid,
+            reveal_deadline,
+            revealed: false,
+        });
+
+        Ok(Event::PurchaseCommitted {
+            lot: self.lot(),
+            buyer,
+            price,
+            reveal_deadline,
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    /// Reveals the sender's commitment and sells the NFT to the earliest
+    /// commitment once it's revealed and the ones before it can't be anymore.
+    pub async fn reveal_purchase(
         &mut self,
         transaction_id: TransactionId,
-        config: &CreateConfig,
+        salt: &[u8; 32],
     ) -> Result<Event, Error> {
-        if matches!(self.status, Status::IsRunning) {
-            return Err(Error::AlreadyRunning);
+        if let Err(error) = self.accept_reveal(salt) {
+            self.credit(msg::source(), msg::value());
+            return Err(error);
         }
+        self.drop_unrevealed();
 
-        let minutes_count = config.duration.hours * 60 + config.duration.minutes;
-        let duration_in_seconds = minutes_count * 60 + config.duration.seconds;
+        match self.commits.first() {
+            Some(commit) if commit.revealed => {
+                let commit = self.commits.remove(0);
+                let result = self
+                    .purchase(
+                        transaction_id,
+                        commit.buyer,
+                        commit.buyer,
+                        commit.price,
+                        commit.paid,
+                    )
+                    .await;
+                if result.is_err() {
+                    self.credit(commit.buyer, commit.paid);
+                }
+                result
+            }
+            _ => Ok(Event::PurchaseRevealed {
+                lot: self.lot(),
+                buyer: msg::source(),
+                timestamp: exec::block_timestamp(),
+            }),
+        }
+    }
 
-        if config.starting_price < config.discount_rate * (duration_in_seconds as u128) {
-            return Err(Error::StartPriceLessThatMinimal);
+    fn accept_reveal(&mut self, salt: &[u8; 32]) -> Result<(), Error> {
+        if !matches!(self.status, Status::IsRunning) {
+            return Err(Error::AlreadyStopped);
+        }
+
+        let buyer = msg::source();
+        let now = self.now();
+        let commit = self
+            .commits
+            .iter_mut()
+            .find(|commit| commit.buyer == buyer)
+            .ok_or(Error::NoCommitment)?;
+        if commit.commitment != commitment(&buyer, salt) {
+            return Err(Error::InvalidReveal);
+        }
+        if !commit.revealed && now > commit.reveal_deadline {
+            return Err(Error::RevealWindowClosed);
+        }
+        let paid = commit.paid + msg::value();
+        if paid < commit.price {
+            return Err(Error::InsufficientMoney);
+        }
+
+        commit.paid = paid;
+        commit.revealed = true;
+        Ok(())
+    }
+
+    /// Forfeits to the seller the deposits of the earliest commitments
+    /// that weren't revealed in time.
+    fn drop_unrevealed(&mut self) {
+        let now = self.now();
+        while let Some(commit) = self.commits.first() {
+            if commit.revealed || now <= commit.reveal_deadline {
+                break;
+            }
+            let commit = self.commits.remove(0);
+            self.credit(self.nft.owner, commit.paid);
+        }
+    }
+
+    pub fn bid(&mut self) -> Result<Event, Error> {
+        let AuctionType::English { min_increment } = self.auction_type else {
+            return Err(Error::WrongAuctionType);
+        };
+        if !matches!(self.status, Status::IsRunning) {
+            return Err(Error::AlreadyStopped);
+        }
+
+        let amount = msg::value();
+        let min_bid = match self.bids.last() {
+            Some(bid) => bid.amount.saturating_add(min_increment.max(1)),
+            None => self.starting_price,
+        };
+        if amount < min_bid {
+            return Err(Error::BidTooLow);
+        }
+
+        if let Some(outbid) = self.bids.pop() {
+            self.credit(outbid.bidder, outbid.deposit);
+        }
+        let bidder = msg::source();
+        self.bids.push(Bid {
+            bidder,
+            amount,
+            deposit: amount,
+            commitment: None,
+        });
+        let extended = self.extend_on_late_bid();
+
+        Ok(Event::BidPlaced {
+            lot: self.lot(),
+            bidder,
+            amount,
+            expires_at: self.expires_at,
+            extended,
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    pub fn seal_bid(&mut self, commitment: Commitment) -> Result<Event, Error> {
+        if !matches!(self.auction_type, AuctionType::SealedBid { .. }) {
+            return Err(Error::WrongAuctionType);
+        }
+        if !matches!(self.status, Status::IsRunning) {
+            return Err(Error::AlreadyStopped);
+        }
+        let bidder = msg::source();
+        if self.bids.iter().any(|bid| bid.bidder == bidder) {
+            return Err(Error::AlreadyCommitted);
+        }
+
+        self.bids.push(Bid {
+            bidder,
+            amount: 0,
+            deposit: msg::value(),
+            commitment: Some(commitment),
+        });
+        let extended = self.extend_on_late_bid();
+
+        Ok(Event::BidSealed {
+            lot: self.lot(),
+            bidder,
+            expires_at: self.expires_at,
+            extended,
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    /// Extends the auction if a bid comes within the anti-sniping threshold of its end,
+    /// at most up to the longest auction. Returns whether the auction was extended.
+    fn extend_on_late_bid(&mut self) -> bool {
+        let Some(config) = &self.anti_sniping else {
+            return false;
+        };
+        let threshold = self.clock(&config.threshold);
+        if self.now().saturating_add(threshold) < self.expires_at {
+            return false;
         }
+        let expires_at = self
+            .expires_at
+            .saturating_add(self.clock(&config.extension))
+            .min(
+                self.started_at
+                    .saturating_add(self.decay_unit.max_duration()),
+            );
+        if expires_at <= self.expires_at {
+            return false;
+        }
+        self.expires_at = expires_at;
+        true
+    }
+
+    pub fn reveal_bid(&mut self, amount: u128, salt: &[u8; 32]) -> Result<Event, Error> {
+        if !matches!(self.auction_type, AuctionType::SealedBid { .. }) {
+            return Err(Error::WrongAuctionType);
+        }
+        if !matches!(self.status, Status::Expired) {
+            return Err(Error::WrongState);
+        }
+        if self.now() >= self.settles_at() {
+            return Err(Error::RevealWindowClosed);
+        }
+
+        let bidder = msg::source();
+        let starting_price = self.starting_price;
+        let bid = self
+            .bids
+            .iter_mut()
+            .find(|bid| bid.bidder == bidder)
+            .ok_or(Error::NoCommitment)?;
+        if bid.commitment != Some(bid_commitment(&bidder, amount, salt)) {
+            return Err(Error::InvalidReveal);
+        }
+        if amount > bid.deposit {
+            return Err(Error::InsufficientMoney);
+        }
+        if amount < starting_price {
+            return Err(Error::BidTooLow);
+        }
+        bid.amount = amount;
+        bid.commitment = None;
+
+        Ok(Event::BidRevealed {
+            lot: self.lot(),
+            bidder,
+            amount,
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    /// Sells the NFT to the highest revealed bid, the earliest one on ties.
+    /// In a sealed-bid auction the winner pays the second highest bid.
+    pub async fn settle(&mut self, transaction_id: TransactionId) -> Result<Event, Error> {
+        if matches!(
+            self.auction_type,
+            AuctionType::Dutch | AuctionType::Reverse { .. } | AuctionType::Gradual { .. }
+        ) {
+            return Err(Error::WrongAuctionType);
+        }
+        if !matches!(self.status, Status::Expired) || self.now() < self.settles_at() {
+            return Err(Error::WrongState);
+        }
+
+        let winner = self
+            .bids
+            .iter()
+            .enumerate()
+            .filter(|(_, bid)| bid.commitment.is_none())
+            .fold(None, |winner, (index, bid)| match winner {
+                Some((_, amount)) if amount >= bid.amount => winner,
+                _ => Some((index, bid.amount)),
+            });
+        let Some((index, _)) = winner else {
+            return Err(Error::NoBids);
+        };
+        let bid = self.bids.remove(index);
+        let price = match self.auction_type {
+            AuctionType::SealedBid { .. } => self
+                .bids
+                .iter()
+                .filter(|bid| bid.commitment.is_none())
+                .map(|bid| bid.amount)
+                .max()
+                .unwrap_or_default()
+                .max(self.starting_price),
+            _ => bid.amount,
+        };
+
+        let result = self
+            .purchase(transaction_id, bid.bidder, bid.bidder, price, bid.deposit)
+            .await;
+        if result.is_err() {
+            self.credit(bid.bidder, bid.deposit);
+        }
+        result
+    }
+
+    /// Time after which the bids can be settled.
+    fn settles_at(&self) -> u64 {
+        match &self.auction_type {
+            AuctionType::SealedBid { reveal_window } => {
+                self.expires_at.saturating_add(self.clock(reveal_window))
+            }
+            _ => self.expires_at,
+        }
+    }
+
+    /// Whether there are bids that may still win the auction.
+    fn has_pending_bids(&self) -> bool {
+        self.bids.iter().any(|bid| bid.commitment.is_none())
+            || (!self.bids.is_empty() && self.now() < self.settles_at())
+    }
+
+    /// Refunds the remaining bids, the deposits of sealed bids that weren't
+    /// revealed in time go to the seller.
+    fn release_bids(&mut self) {
+        let reveal_closed = self.now() >= self.settles_at();
+        for bid in mem::take(&mut self.bids) {
+            let to = if bid.commitment.is_some() && reveal_closed {
+                self.nft.owner
+            } else {
+                bid.bidder
+            };
+            self.credit(to, bid.deposit);
+        }
+    }
+
+    /// Refunds the remaining commitments, the deposits of the ones that
+    /// weren't revealed in time go to the seller.
+    fn release_commits(&mut self) {
+        let now = self.now();
+        for commit in mem::take(&mut self.commits) {
+            let to = if !commit.revealed && now > commit.reveal_deadline {
+                self.nft.owner
+            } else {
+                commit.buyer
+            };
+            self.credit(to, commit.paid);
+        }
+    }
+
+    pub fn token_price(&self) -> u128 {
+        let now = self.now();
+        match self.auction_type {
+            AuctionType::Reverse { budget } => self.schedule().offer_at(now, budget),
+            _ => self.schedule().price_at(now),
+        }
+    }
+
+    pub fn schedule(&self) -> PriceSchedule {
+        PriceSchedule {
+            starting_price: self.starting_price,
+            discount_rate: self.discount_rate,
+            min_price: self.min_price,
+            started_at: self.started_at,
+            decay_unit: self.decay_unit,
+            buy_now_price: self.buy_now_price,
+        }
+    }
+
+    pub async fn renew_contract(
+        &mut self,
+        transaction_id: TransactionId,
+        config: &CreateConfig,
+    ) -> Result<Event, Error> {
+        self.ensure_finished()?;
+        let duration = Self::validate_config(config)?;
         self.validate_nft_approve(config.nft_contract_actor_id, config.token_id)
             .await?;
-        self.status = Status::IsRunning;
-        self.started_at = exec::block_timestamp();
-        self.expires_at = self.started_at + duration_in_seconds * 1000;
+        self.validate_queue(config).await?;
+        let owner = Self::get_token_owner(config.nft_contract_actor_id, config.token_id).await?;
+        self.release_offers(|_| true);
+
+        self.start(config, duration);
         self.nft.token_id = config.token_id;
         self.nft.contract_id = config.nft_contract_actor_id;
-        self.nft.owner =
-            Self::get_token_owner(config.nft_contract_actor_id, config.token_id).await?;
-
-        self.discount_rate = config.discount_rate;
-        self.starting_price = config.starting_price;
+        self.nft.owner = owner;
+        if matches!(self.auction_type, AuctionType::Gradual { .. }) {
+            return Ok(self.started());
+        }
 
         msg::send_for_reply(
             self.nft.contract_id,
@@ -131,10 +792,215 @@ impl Auction {
         .expect("Send NFTAction::Transfer at renew contract")
         .await
         .map_err(|_e| Error::NftTransferFailed)?;
-        Ok(Event::AuctionStarted {
-            token_owner: self.owner,
-            price: self.starting_price,
-            token_id: self.nft.token_id,
+        Ok(self.started())
+    }
+
+    /// A new auction can only replace one that has nothing left to settle:
+    /// an expired auction still holds the NFT and a purchased one the reward.
+    fn ensure_finished(&self) -> Result<(), Error> {
+        match self.status {
+            Status::IsRunning => Err(Error::AlreadyRunning),
+            Status::Expired | Status::Purchased { .. } => Err(Error::WrongState),
+            Status::None | Status::Rewarded { .. } | Status::Stopped | Status::Reclaimed => Ok(()),
+        }
+    }
+
+    /// Restarts an expired auction for the NFT the program still holds,
+    /// keeping the original seller as the token owner.
+    pub fn relist(&mut self, config: &CreateConfig) -> Result<Event, Error> {
+        if !matches!(self.status, Status::Expired) {
+            return Err(Error::WrongState);
+        }
+        if matches!(self.auction_type, AuctionType::Reverse { .. })
+            || matches!(self.auction_type, AuctionType::Gradual { .. })
+                != matches!(config.auction_type, AuctionType::Gradual { .. })
+        {
+            return Err(Error::WrongAuctionType);
+        }
+        if msg::source().ne(&self.nft.owner) {
+            return Err(Error::NotSeller);
+        }
+        if config.nft_contract_actor_id != self.nft.contract_id
+            || config.token_id != self.nft.token_id
+            || matches!(&config.auction_type, AuctionType::Gradual { token_ids, .. } if *token_ids != self.queue)
+        {
+            return Err(Error::WrongToken);
+        }
+        if self.has_pending_bids() {
+            return Err(Error::BidsPending);
+        }
+
+        let duration = Self::validate_config(config)?;
+        self.record(Outcome::Expired);
+        self.start(config, duration);
+
+        Ok(self.started())
+    }
+
+    /// Checks the config and returns the auction duration on its clock.
+    fn validate_config(config: &CreateConfig) -> Result<u64, Error> {
+        if matches!(config.auction_type, AuctionType::Reverse { .. }) {
+            return Err(Error::WrongAuctionType);
+        }
+        if config.nft_contract_actor_id.is_zero()
+            || config.nft_contract_actor_id == exec::program_id()
+        {
+            return Err(Error::InvalidNftContract);
+        }
+        if config.starting_price == 0 {
+            return Err(Error::ZeroStartingPrice);
+        }
+        if config.fixed_price && config.discount_rate != 0 {
+            return Err(Error::FixedPriceWithDiscount);
+        }
+        // Bidding auctions don't decay the price
+        if !config.fixed_price
+            && config.discount_rate == 0
+            && matches!(
+                config.auction_type,
+                AuctionType::Dutch | AuctionType::Gradual { .. }
+            )
+        {
+            return Err(Error::ZeroDiscountRate);
+        }
+        // Purchases are only committed to in plain Dutch auctions
+        if config.commit_reveal.is_some() && config.auction_type != AuctionType::Dutch {
+            return Err(Error::WrongAuctionType);
+        }
+        if let Some(commit_reveal) = &config.commit_reveal {
+            if commit_reveal.deposit < MIN_OFFER_DEPOSIT {
+                return Err(Error::InvalidCommitDeposit);
+            }
+        }
+
+        let unit = config.decay_unit;
+        let duration = Self::auction_duration(unit, &config.duration)?;
+        let mut durations = Vec::new();
+        if let Some(commit_reveal) = &config.commit_reveal {
+            durations.push(&commit_reveal.reveal_window);
+        }
+        if let AuctionType::SealedBid { reveal_window } = &config.auction_type {
+            durations.push(reveal_window);
+        }
+        if let Some(anti_sniping) = &config.anti_sniping {
+            durations.extend([&anti_sniping.threshold, &anti_sniping.extension]);
+        }
+        if durations
+            .into_iter()
+            .any(|duration| unit.duration(duration).is_none())
+        {
+            return Err(Error::InvalidDuration);
+        }
+
+        if config.starting_price < Self::total_discount(config.discount_rate, unit, duration)? {
+            return Err(Error::StartPriceLessThatMinimal);
+        }
+        if config.min_price > config.starting_price {
+            return Err(Error::InvalidMinPrice);
+        }
+        if matches!(config.buy_now_price, Some(price) if price == 0
+            || price < config.min_price
+            || price > config.starting_price)
+        {
+            return Err(Error::InvalidBuyNowPrice);
+        }
+        let mut recipients = BTreeSet::new();
+        if config.proceeds_splits.len() > MAX_PROCEEDS_SPLITS
+            || !config
+                .proceeds_splits
+                .iter()
+                .all(|(recipient, _)| !recipient.is_zero() && recipients.insert(*recipient))
+        {
+            return Err(Error::InvalidProceedsSplits);
+        }
+        if !config.proceeds_splits.is_empty()
+            && config
+                .proceeds_splits
+                .iter()
+                .map(|(_, share)| u32::from(*share))
+                .sum::<u32>()
+                != u32::from(BASIS_POINTS)
+        {
+            return Err(Error::InvalidProceedsSplits);
+        }
+        Ok(duration)
+    }
+
+    /// Length of `duration` on the `unit` clock for an auction starting now.
+    fn auction_duration(unit: DecayUnit, duration: &Duration) -> Result<u64, Error> {
+        let duration = unit
+            .duration(duration)
+            .filter(|duration| unit.now().checked_add(*duration).is_some())
+            .ok_or(Error::InvalidDuration)?;
+        if duration == 0 {
+            return Err(Error::ZeroDuration);
+        }
+        if duration > unit.max_duration() {
+            return Err(Error::DurationTooLong);
+        }
+        Ok(duration)
+    }
+
+    /// Price decrease over `duration` of the `unit` clock.
+    fn total_discount(discount_rate: u128, unit: DecayUnit, duration: u64) -> Result<u128, Error> {
+        discount_rate
+            .checked_mul(unit.steps(duration).into())
+            .ok_or(Error::PriceOverflow)
+    }
+
+    fn start(&mut self, config: &CreateConfig, duration: u64) {
+        self.auction_id = self.auction_id.wrapping_add(1);
+        self.status = Status::IsRunning;
+        self.decay_unit = config.decay_unit;
+        self.started_at = self.now();
+        self.expires_at = self.started_at.saturating_add(duration);
+        self.discount_rate = config.discount_rate;
+        self.starting_price = config.starting_price;
+        self.min_price = config.min_price;
+        self.proceeds_splits = config.proceeds_splits.clone();
+        self.commit_reveal = config.commit_reveal.clone();
+        self.auction_type = config.auction_type.clone();
+        self.anti_sniping = config.anti_sniping.clone();
+        self.buy_now_price = config.buy_now_price;
+        self.queue = match &config.auction_type {
+            AuctionType::Gradual { token_ids, .. } => token_ids.clone(),
+            _ => Vec::new(),
+        };
+    }
+
+    pub fn extend(&mut self, duration: &Duration) -> Result<Event, Error> {
+        if !matches!(self.status, Status::IsRunning) {
+            return Err(Error::AlreadyStopped);
+        }
+        if msg::source().ne(&self.nft.owner) {
+            return Err(Error::NotSeller);
+        }
+
+        let expires_at = self
+            .decay_unit
+            .duration(duration)
+            .and_then(|duration| self.expires_at.checked_add(duration))
+            .ok_or(Error::InvalidDuration)?;
+        if expires_at - self.started_at > self.decay_unit.max_duration() {
+            return Err(Error::DurationTooLong);
+        }
+
+        if !matches!(self.auction_type, AuctionType::Reverse { .. })
+            && self.starting_price
+                < Self::total_discount(
+                    self.discount_rate,
+                    self.decay_unit,
+                    expires_at - self.started_at,
+                )?
+        {
+            return Err(Error::StartPriceLessThatMinimal);
+        }
+        self.expires_at = expires_at;
+
+        Ok(Event::AuctionExtended {
+            lot: self.lot(),
+            expires_at,
+            timestamp: exec::block_timestamp(),
         })
     }
 
@@ -147,11 +1013,84 @@ impl Auction {
             return Err(Error::IncorrectRewarder);
         }
 
-        if let Err(_e) = msg::send(self.nft.owner, "REWARD", price) {
-            return Err(Error::RewardSendFailed);
-        }
+        self.pay_proceeds(price);
         self.status = Status::Rewarded { price };
-        Ok(Event::Rewarded { price })
+        Ok(Event::Rewarded {
+            lot: self.lot(),
+            buyer: self.buyer,
+            price,
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    /// Sends `amount` to the proceeds recipients, crediting the ones that
+    /// can't be paid right away.
+    fn pay_proceeds(&mut self, amount: u128) {
+        let mut payouts = Vec::with_capacity(self.proceeds_splits.len().max(1));
+        let mut rest = amount;
+        if let Some(((last, _), splits)) = self.proceeds_splits.split_last() {
+            for (recipient, share) in splits {
+                let payout =
+                    (U256::from(amount) * U256::from(*share) / U256::from(BASIS_POINTS)).as_u128();
+                rest -= payout;
+                payouts.push((*recipient, payout));
+            }
+            payouts.push((*last, rest));
+        } else {
+            payouts.push((self.nft.owner, rest));
+        }
+
+        for (recipient, payout) in payouts {
+            if payout > 0 && msg::send(recipient, "REWARD", payout).is_err() {
+                self.credit(recipient, payout);
+            }
+        }
+    }
+
+    pub fn withdraw(&mut self) -> Result<(Event, u128), Error> {
+        let amount = self
+            .balances
+            .remove(&msg::source())
+            .ok_or(Error::NothingToWithdraw)?;
+
+        Ok((
+            Event::Withdrawn {
+                actor: msg::source(),
+                amount,
+                timestamp: exec::block_timestamp(),
+            },
+            amount,
+        ))
+    }
+
+    /// Adds `amount` to the balance `actor` can later pull with [`Action::Withdraw`].
+    pub fn credit(&mut self, actor: ActorId, amount: u128) {
+        if amount == 0 {
+            return;
+        }
+        *self.balances.entry(actor).or_default() += amount;
+    }
+
+    /// Checks that the NFTs queued after the first one in a gradual auction
+    /// are approved to the program and belong to the same seller.
+    async fn validate_queue(&self, config: &CreateConfig) -> Result<(), Error> {
+        let AuctionType::Gradual { token_ids, .. } = &config.auction_type else {
+            return Ok(());
+        };
+        let mut queued = BTreeSet::from([config.token_id]);
+        if !token_ids.iter().all(|token_id| queued.insert(*token_id)) {
+            return Err(Error::WrongToken);
+        }
+
+        let seller = Self::get_token_owner(config.nft_contract_actor_id, config.token_id).await?;
+        for token_id in token_ids {
+            self.validate_nft_approve(config.nft_contract_actor_id, *token_id)
+                .await?;
+            if Self::get_token_owner(config.nft_contract_actor_id, *token_id).await? != seller {
+                return Err(Error::WrongToken);
+            }
+        }
+        Ok(())
     }
 
     pub async fn get_token_owner(contract_id: ActorId, token_id: U256) -> Result<ActorId, Error> {
@@ -190,10 +1129,13 @@ impl Auction {
         Ok(())
     }
 
+    /// Expires the auction once `expires_at`, extended by late bids, has passed.
     pub fn stop_if_time_is_over(&mut self) {
-        if matches!(self.status, Status::IsRunning) && exec::block_timestamp() >= self.expires_at {
-            self.status = Status::Expired;
-        }
+        self.status = self.effective_status();
+    }
+
+    pub fn effective_status(&self) -> Status {
+        self.status.effective(self.expires_at, self.now())
     }
 
     pub async fn force_stop(&mut self, transaction_id: TransactionId) -> Result<Event, Error> {
@@ -205,13 +1147,19 @@ impl Auction {
         }
 
         let stopped = Event::AuctionStopped {
-            token_owner: self.owner,
-            token_id: self.nft.token_id,
+            lot: self.lot(),
+            timestamp: exec::block_timestamp(),
         };
         if let Status::Rewarded { price: _ } = self.status {
             return Ok(stopped);
         }
-        if let Err(_e) = msg::send_for_reply(
+        if let AuctionType::Reverse { budget } = self.auction_type {
+            if matches!(self.status, Status::IsRunning | Status::Expired) {
+                self.credit(self.nft.owner, budget);
+            }
+        } else if matches!(self.auction_type, AuctionType::Gradual { .. }) {
+            // The NFTs of gradual auctions never leave the seller
+        } else if let Err(_e) = msg::send_for_reply(
             self.nft.contract_id,
             NFTAction::Transfer {
                 transaction_id,
@@ -227,14 +1175,174 @@ impl Auction {
             return Err(Error::NftTransferFailed);
         }
 
+        if matches!(self.status, Status::IsRunning | Status::Expired) {
+            self.record(Outcome::Stopped);
+        }
         self.status = Status::Stopped;
 
         Ok(stopped)
     }
 
-    pub fn info(&mut self) -> AuctionInfo {
-        self.stop_if_time_is_over();
+    pub async fn reclaim_nft(&mut self, transaction_id: TransactionId) -> Result<Event, Error> {
+        if !matches!(self.status, Status::Expired) {
+            return Err(Error::WrongState);
+        }
+        if matches!(self.auction_type, AuctionType::Reverse { .. }) {
+            return Err(Error::WrongAuctionType);
+        }
+        if msg::source().ne(&self.nft.owner) {
+            return Err(Error::NotSeller);
+        }
+        if self.has_pending_bids() {
+            return Err(Error::BidsPending);
+        }
+
+        if !matches!(self.auction_type, AuctionType::Gradual { .. }) {
+            msg::send_for_reply(
+                self.nft.contract_id,
+                NFTAction::Transfer {
+                    transaction_id,
+                    to: self.nft.owner,
+                    token_id: self.nft.token_id,
+                },
+                0,
+                0,
+            )
+            .expect("Can't send NFTAction::Transfer at reclaim NFT")
+            .await
+            .map_err(|_e| Error::NftTransferFailed)?;
+        }
+
+        self.status = Status::Reclaimed;
+        self.record(Outcome::Reclaimed);
+
+        Ok(Event::NftReclaimed {
+            lot: self.lot(),
+            timestamp: exec::block_timestamp(),
+        })
+    }
+
+    /// Starts a reverse auction on behalf of the sender with the attached value as its budget.
+    pub fn create_reverse(&mut self, config: &ReverseConfig) -> Result<Event, Error> {
+        self.ensure_finished()?;
+        let budget = msg::value();
+        if budget < config.starting_price {
+            return Err(Error::InsufficientMoney);
+        }
+        let duration = Self::auction_duration(config.decay_unit, &config.duration)?;
+        self.release_offers(|_| true);
+
+        self.start(
+            &CreateConfig {
+                starting_price: config.starting_price,
+                discount_rate: config.increase_rate,
+                auction_type: AuctionType::Reverse { budget },
+                decay_unit: config.decay_unit,
+                ..Default::default()
+            },
+            duration,
+        );
+        self.nft = Nft {
+            owner: msg::source(),
+            ..Default::default()
+        };
+
+        Ok(self.started())
+    }
+
+    /// Ends the reverse auction paying the current offer to the sender,
+    /// the rest of the budget is credited to the creator.
+    pub fn accept(&mut self) -> Result<(Event, u128), Error> {
+        let AuctionType::Reverse { budget } = self.auction_type else {
+            return Err(Error::WrongAuctionType);
+        };
+        if !matches!(self.status, Status::IsRunning) {
+            return Err(Error::AlreadyStopped);
+        }
+
+        let price = self.token_price();
+        self.status = Status::Rewarded { price };
+        self.buyer = msg::source();
+        self.record(Outcome::Sold { price });
+        self.credit(self.nft.owner, budget - price);
+
+        Ok((
+            Event::Accepted {
+                lot: self.lot(),
+                provider: self.buyer,
+                price,
+                timestamp: exec::block_timestamp(),
+            },
+            price,
+        ))
+    }
+
+    pub fn reclaim_budget(&mut self) -> Result<(Event, u128), Error> {
+        let AuctionType::Reverse { budget } = self.auction_type else {
+            return Err(Error::WrongAuctionType);
+        };
+        if !matches!(self.status, Status::Expired) {
+            return Err(Error::WrongState);
+        }
+        if msg::source().ne(&self.nft.owner) {
+            return Err(Error::NotSeller);
+        }
+
+        self.status = Status::Reclaimed;
+        self.record(Outcome::Reclaimed);
+
+        Ok((
+            Event::BudgetReclaimed {
+                lot: self.lot(),
+                amount: budget,
+                timestamp: exec::block_timestamp(),
+            },
+            budget,
+        ))
+    }
+
+    /// Appends the current auction to the history with the given outcome.
+    fn record(&mut self, outcome: Outcome) {
+        let sold = matches!(outcome, Outcome::Sold { .. });
+        let buyer = sold.then_some(self.buyer);
+        self.history.push(AuctionRecord {
+            lot: self.lot(),
+            buyer,
+            started_at: self.started_at,
+            ended_at: self.now(),
+            outcome,
+            decay_unit: self.decay_unit,
+        });
+        if self.history.len() > MAX_HISTORY_RECORDS {
+            self.history.remove(0);
+        }
+        self.release_commits();
+        self.release_bids();
+        // Offers made with `MakeOffer` outlive the unsold auction
+        self.release_offers(|offer| sold || offer.expires_at.is_none());
+    }
+
+    pub fn started(&self) -> Event {
+        Event::AuctionStarted {
+            lot: self.lot(),
+            price: self.starting_price,
+            expires_at: self.expires_at,
+            timestamp: exec::block_timestamp(),
+        }
+    }
+
+    pub fn lot(&self) -> Lot {
+        Lot {
+            auction_id: self.auction_id,
+            nft_contract_id: self.nft.contract_id,
+            token_id: self.nft.token_id,
+            seller: self.nft.owner,
+        }
+    }
+
+    pub fn info(&self) -> AuctionInfo {
         AuctionInfo {
+            auction_id: self.auction_id,
             nft_contract_actor_id: self.nft.contract_id,
             token_id: self.nft.token_id,
             token_owner: self.nft.owner,
@@ -242,19 +1350,61 @@ impl Auction {
             starting_price: self.starting_price,
             current_price: self.token_price(),
             discount_rate: self.discount_rate,
-            time_left: self.expires_at.saturating_sub(exec::block_timestamp()),
+            min_price: self.min_price,
+            started_at: self.started_at,
+            time_left: self.expires_at.saturating_sub(self.now()),
             expires_at: self.expires_at,
-            status: self.status.clone(),
+            status: self.effective_status(),
+            proceeds_splits: self.proceeds_splits.clone(),
+            commit_reveal: self.commit_reveal.clone(),
+            auction_type: self.auction_type.clone(),
+            anti_sniping: self.anti_sniping.clone(),
+            queued_tokens: self.queue.clone(),
+            buy_now_price: self.buy_now_price,
+            decay_unit: self.decay_unit,
+        }
+    }
+
+    pub fn query(&self, query: StateQuery) -> StateReply {
+        match query {
+            StateQuery::Balance(actor) => {
+                StateReply::Balance(self.balances.get(&actor).copied().unwrap_or_default())
+            }
+            StateQuery::Commits => StateReply::Commits(self.commits.clone()),
+            StateQuery::Bids => StateReply::Bids(self.bids.clone()),
+            StateQuery::Offers => StateReply::Offers(
+                self.offers
+                    .iter()
+                    .map(|(offerer, offer)| (*offerer, offer.clone()))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn state(&self) -> AuctionState {
+        AuctionState {
+            info: self.info(),
             transactions: self.transactions.clone(),
             current_tid: self.current_tid,
+            dust_threshold: self.dust_threshold,
+            dust_destination: self.dust_destination,
+            history: self.history.clone(),
         }
     }
 }
 
 #[no_mangle]
 extern "C" fn init() {
+    let config: InitConfig = msg::load().expect("Unable to decode InitConfig");
+    // Larger refunds are the buyer's money, not dust
+    assert!(
+        config.dust_threshold <= EXISTENTIAL_DEPOSIT,
+        "Dust threshold exceeds the existential deposit"
+    );
     let auction = Auction {
         owner: msg::source(),
+        dust_threshold: config.dust_threshold,
+        dust_destination: config.dust_destination,
         ..Default::default()
     };
 
@@ -266,6 +1416,14 @@ async fn main() {
     let action: Action = msg::load().expect("Could not load Action");
     let auction: &mut Auction = unsafe { AUCTION.get_or_insert(Auction::default()) };
 
+    if let Action::Query(query) = action {
+        auction.credit(msg::source(), msg::value());
+        let result: Result<VersionedEvent, Error> =
+            Ok(Event::StateReply(auction.query(query)).into());
+        reply(result, 0).expect("Failed to encode or reply with `Result<VersionedEvent, Error>`");
+        return;
+    }
+
     auction.stop_if_time_is_over();
 
     let msg_source = msg::source();
@@ -277,6 +1435,7 @@ async fn main() {
     }) = auction.transactions.get(&msg_source)
     {
         if action != *pend_action {
+            auction.credit(msg_source, msg::value());
             reply(r, 0).expect("Failed to encode or reply with `Result<Action, Error>`");
             return;
         }
@@ -293,16 +1452,22 @@ async fn main() {
         auction.current_tid = auction.current_tid.wrapping_add(1);
         transaction_id
     };
+    if !takes_value(&action) {
+        auction.credit(msg_source, msg::value());
+    }
 
     let (result, value) = match &action {
-        Action::Buy => {
-            let reply = auction.buy(transaction_id).await;
-            let result = match reply {
-                Ok((event, refund)) => (Ok(event), refund),
-                Err(_e) => (Err(_e), 0),
+        Action::Buy { max_price } | Action::BuyFor { max_price, .. } => {
+            let recipient = match action {
+                Action::BuyFor { recipient, .. } => recipient,
+                _ => msg_source,
             };
+            let result = auction.buy(transaction_id, recipient, *max_price).await;
+            if result.is_err() {
+                auction.credit(msg_source, msg::value());
+            }
             auction.transactions.remove(&msg_source);
-            result
+            (result, 0)
         }
         Action::Create(config) => {
             let result = (auction.renew_contract(transaction_id, config).await, 0);
@@ -319,16 +1484,160 @@ async fn main() {
             auction.transactions.remove(&msg_source);
             result
         }
+        Action::ExtendAuction { duration } => {
+            let result = (auction.extend(duration), 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::Relist(config) => {
+            let result = (auction.relist(config), 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::ReclaimNft => {
+            let result = (auction.reclaim_nft(transaction_id).await, 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::CommitPurchase { commitment } => {
+            let result = auction.commit_purchase(*commitment);
+            if result.is_err() {
+                auction.credit(msg_source, msg::value());
+            }
+            auction.transactions.remove(&msg_source);
+            (result, 0)
+        }
+        Action::RevealPurchase { salt } => {
+            let result = (auction.reveal_purchase(transaction_id, salt).await, 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::Bid => {
+            let result = auction.bid();
+            if result.is_err() {
+                auction.credit(msg_source, msg::value());
+            }
+            auction.transactions.remove(&msg_source);
+            (result, 0)
+        }
+        Action::SealBid { commitment } => {
+            let result = auction.seal_bid(*commitment);
+            if result.is_err() {
+                auction.credit(msg_source, msg::value());
+            }
+            auction.transactions.remove(&msg_source);
+            (result, 0)
+        }
+        Action::RevealBid { amount, salt } => {
+            let result = (auction.reveal_bid(*amount, salt), 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::Settle => {
+            let result = (auction.settle(transaction_id).await, 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::CreateReverse(config) => {
+            let result = auction.create_reverse(config);
+            if result.is_err() {
+                auction.credit(msg_source, msg::value());
+            }
+            auction.transactions.remove(&msg_source);
+            (result, 0)
+        }
+        Action::Accept => {
+            let result = auction.accept();
+            auction.transactions.remove(&msg_source);
+            match result {
+                Ok((event, amount)) => (Ok(event), amount),
+                Err(e) => (Err(e), 0),
+            }
+        }
+        Action::ReclaimBudget => {
+            let result = auction.reclaim_budget();
+            auction.transactions.remove(&msg_source);
+            match result {
+                Ok((event, amount)) => (Ok(event), amount),
+                Err(e) => (Err(e), 0),
+            }
+        }
+        Action::PlaceOffer { price } => {
+            let result = auction.place_offer(*price);
+            if result.is_err() {
+                auction.credit(msg_source, msg::value());
+            }
+            auction.transactions.remove(&msg_source);
+            (result, 0)
+        }
+        Action::FillOffer => {
+            let result = (auction.fill_offer(transaction_id).await, 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::MakeOffer { duration } => {
+            let result = auction.make_offer(duration);
+            if result.is_err() {
+                auction.credit(msg_source, msg::value());
+            }
+            auction.transactions.remove(&msg_source);
+            (result, 0)
+        }
+        Action::AcceptOffer { offerer } => {
+            let result = (auction.accept_offer(transaction_id, *offerer).await, 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::RejectOffer { offerer } => {
+            let result = (auction.reject_offer(*offerer), 0);
+            auction.transactions.remove(&msg_source);
+            result
+        }
+        Action::WithdrawOffer => {
+            let result = auction.withdraw_offer();
+            auction.transactions.remove(&msg_source);
+            match result {
+                Ok((event, amount)) => (Ok(event), amount),
+                Err(e) => (Err(e), 0),
+            }
+        }
+        Action::Withdraw => {
+            let result = auction.withdraw();
+            auction.transactions.remove(&msg_source);
+            match result {
+                Ok((event, amount)) => (Ok(event), amount),
+                Err(e) => (Err(e), 0),
+            }
+        }
+        Action::Query(_) => unreachable!("Queries are replied to before caching"),
     };
-    reply(result, value).expect("Failed to encode or reply with `Result<Event, Error>`");
+    reply(result.map(VersionedEvent::from), value)
+        .expect("Failed to encode or reply with `Result<VersionedEvent, Error>`");
+}
+
+/// Actions that use the attached value, the value of any other action is
+/// credited back to the sender.
+fn takes_value(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::Buy { .. }
+            | Action::BuyFor { .. }
+            | Action::CommitPurchase { .. }
+            | Action::RevealPurchase { .. }
+            | Action::Bid
+            | Action::SealBid { .. }
+            | Action::CreateReverse(_)
+            | Action::PlaceOffer { .. }
+            | Action::MakeOffer { .. }
+    )
 }
 
 fn common_state() -> <AuctionMetadata as Metadata>::State {
-    static_mut_state().info()
+    static_state().state()
 }
 
-fn static_mut_state() -> &'static mut Auction {
-    unsafe { AUCTION.get_or_insert(Default::default()) }
+fn static_state() -> &'static Auction {
+    unsafe { AUCTION.as_ref().expect("Program is not initialized") }
 }
 
 #[no_mangle]
@@ -1,21 +1,122 @@
 #![no_std]
 
 use auction_io::{
-    auction::{AuctionInfo, Status},
+    auction::{
+        Action, AuctionInfo, AuctionRecord, AuctionType, HistoryFilter, Status, Transaction,
+    },
     io::AuctionMetadata,
 };
 use gmeta::{metawasm, Metadata};
-use gstd::{exec, prelude::*};
+use gstd::{prelude::*, ActorId};
+use primitive_types::U256;
 
 #[metawasm]
 pub mod metafns {
     pub type State = <AuctionMetadata as Metadata>::State;
 
-    pub fn info(mut state: State) -> AuctionInfo {
-        if matches!(state.status, Status::IsRunning) && exec::block_timestamp() >= state.expires_at
-        {
-            state.status = Status::Expired
+    pub fn info(state: State) -> AuctionInfo {
+        actual(state.info)
+    }
+
+    pub fn current_price(state: State) -> u128 {
+        state.info.price_at(state.info.decay_unit.now())
+    }
+
+    pub fn price_at(state: State, timestamp: u64) -> u128 {
+        state.info.price_at(timestamp)
+    }
+
+    /// Price points every `interval` units of the auction clock from now until it expires.
+    pub fn price_schedule(state: State, interval: u64) -> Vec<(u64, u128)> {
+        state
+            .info
+            .price_points(state.info.decay_unit.now(), state.info.expires_at, interval)
+    }
+
+    /// Time left until the price drops to `target`, or `None` if it doesn't
+    /// happen before the auction expires or the auction is a reverse one.
+    pub fn time_until_price(state: State, target: u128) -> Option<u64> {
+        if matches!(state.info.auction_type, AuctionType::Reverse { .. }) {
+            return None;
         }
         state
+            .info
+            .schedule()
+            .reaches_price_at(target)
+            .filter(|timestamp| *timestamp < state.info.expires_at)
+            .map(|timestamp| timestamp.saturating_sub(state.info.decay_unit.now()))
+    }
+
+    /// Price at `timestamp` of the next NFT of a gradual auction if the
+    /// current one is sold at `sold_at`, or `None` if there is no next NFT.
+    pub fn price_after_sale(state: State, sold_at: u64, timestamp: u64) -> Option<u128> {
+        let AuctionType::Gradual { price_bump, .. } = state.info.auction_type else {
+            return None;
+        };
+        let schedule = state.info.schedule();
+        (!state.info.queued_tokens.is_empty()).then(|| {
+            schedule
+                .after_sale(schedule.price_at(sold_at), sold_at, price_bump)
+                .price_at(timestamp)
+        })
+    }
+
+    pub fn is_active(state: State) -> bool {
+        matches!(actual(state.info).status, Status::IsRunning)
+    }
+
+    pub fn pending_transactions(state: State) -> Vec<(ActorId, Transaction<Action>)> {
+        state.transactions.into_iter().collect()
     }
+
+    pub fn pending_transaction(state: State, actor: ActorId) -> Option<Transaction<Action>> {
+        state.transactions.get(&actor).cloned()
+    }
+
+    pub fn auction_by_seller(state: State, seller: ActorId) -> Option<AuctionInfo> {
+        let info = state.info;
+        (info.auction_id != 0 && info.token_owner == seller).then(|| actual(info))
+    }
+
+    pub fn auction_by_token(
+        state: State,
+        nft_contract_id: ActorId,
+        token_id: U256,
+    ) -> Option<AuctionInfo> {
+        let info = state.info;
+        (info.auction_id != 0
+            && info.nft_contract_actor_id == nft_contract_id
+            && info.token_id == token_id)
+            .then(|| actual(info))
+    }
+
+    /// Returns `page_size` finished auctions starting from `page * page_size`, oldest first.
+    pub fn history(state: State, page: u32, page_size: u32) -> Vec<AuctionRecord> {
+        HistoryFilter::All.page(&state.history, page, page_size)
+    }
+
+    pub fn history_by_seller(
+        state: State,
+        seller: ActorId,
+        page: u32,
+        page_size: u32,
+    ) -> Vec<AuctionRecord> {
+        HistoryFilter::Seller(seller).page(&state.history, page, page_size)
+    }
+
+    pub fn history_by_collection(
+        state: State,
+        nft_contract_id: ActorId,
+        page: u32,
+        page_size: u32,
+    ) -> Vec<AuctionRecord> {
+        HistoryFilter::Collection(nft_contract_id).page(&state.history, page, page_size)
+    }
+}
+
+fn actual(mut info: AuctionInfo) -> AuctionInfo {
+    info.status = info
+        .status
+        .effective(info.expires_at, info.decay_unit.now());
+    info
 }
@@ -1,6 +1,11 @@
-use auction_io::auction::{Action, Error, Event};
-use gstd::{ActorId, Encode};
-use gtest::{Log, System};
+use auction_io::auction::{
+    bid_commitment, commitment, Action, AntiSnipingConfig, AuctionInfo, AuctionState, AuctionType,
+    CommitRevealConfig, CreateConfig, DecayUnit, Duration, DustDestination, Error, Event,
+    HistoryFilter, InitConfig, Lot, ReverseConfig, StateQuery, StateReply, Status,
+    EXISTENTIAL_DEPOSIT, MAX_COMMITS, MAX_PROCEEDS_SPLITS, MIN_OFFER_DEPOSIT,
+};
+use gstd::ActorId;
+use gtest::{Log, Program, System};
 mod routines;
 use routines::*;
 
@@ -12,16 +17,29 @@ fn buy() {
 
     let nft_program = sys.get_program(2);
     let token_id: u64 = 0;
-    let result = auction.send_with_value(USERS[1], Action::Buy, 1_000_000_000);
-
-    println!("{:?}", result.decoded_log::<Result<Event, Error>>());
-    assert!(result.contains(&(
+    let result = auction.send_with_value(
         USERS[1],
-        Ok::<Event, Error>(Event::Bought {
-            price: 1_000_000_000,
-        })
-        .encode()
-    )));
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+
+    let Ok(Event::Bought { lot, buyer, price, dust, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(
+        lot,
+        Lot {
+            auction_id: 1,
+            nft_contract_id: 2.into(),
+            token_id: token_id.into(),
+            seller: USERS[0].into(),
+        }
+    );
+    assert_eq!(buyer, USERS[1].into());
+    assert_eq!(price, 1_000_000_000);
+    assert_eq!(dust, 0);
 
     let res = nft_owner(&nft_program, USERS[0], token_id.into());
     let new_owner = ActorId::from(USERS[1]);
@@ -53,12 +71,22 @@ fn buy_later_with_lower_price() {
 
     let auction = init(&sys);
     sys.spend_blocks(100_000);
-    let result = auction.send_with_value(USERS[1], Action::Buy, 900_000_000);
-
-    assert!(result.contains(&(
+    let result = auction.send_with_value(
         USERS[1],
-        Ok::<Event, Error>(Event::Bought { price: 900_000_000 }).encode()
-    )));
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        900_000_000,
+    );
+
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 900_000_000,
+            dust: 0,
+            ..
+        })
+    ));
 
     sys.claim_value_from_mailbox(USERS[0]);
 
@@ -77,13 +105,22 @@ fn buy_two_times() {
     let sys = System::new();
 
     let auction = init(&sys);
-    auction.send_with_value(USERS[1], Action::Buy, 1_000_000_000);
-    let result = auction.send_with_value(USERS[2], Action::Buy, 1_000_000_000);
-    println!("{:?}", result.decoded_log::<Result<Event, Error>>());
-    assert!(result.contains(&(
+    auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    let result = auction.send_with_value(
         USERS[2],
-        Err::<Event, Error>(Error::AlreadyStopped).encode()
-    )));
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    println!("{:?}", reply(&result));
+    assert_eq!(reply(&result), Err(Error::AlreadyStopped));
 }
 
 #[test]
@@ -92,12 +129,15 @@ fn buy_too_late() {
 
     let auction = init(&sys);
     sys.spend_blocks(DURATION);
-    let result = auction.send_with_value(USERS[1], Action::Buy, 1_000_000_000);
-
-    assert!(result.contains(&(
+    let result = auction.send_with_value(
         USERS[1],
-        Err::<Event, Error>(Error::AlreadyStopped).encode()
-    )));
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+
+    assert_eq!(reply(&result), Err(Error::AlreadyStopped));
 }
 
 #[test]
@@ -105,12 +145,63 @@ fn buy_with_less_money() {
     let sys = System::new();
 
     let auction = init(&sys);
-    let result = auction.send_with_value(USERS[1], Action::Buy, 999_000_000);
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        999_000_000,
+    );
+
+    assert_eq!(reply(&result), Err(Error::InsufficientMoney));
+}
+
+#[test]
+fn buy_above_max_price() {
+    let sys = System::new();
+    let buyer = USERS[1];
+
+    let auction = init(&sys);
+    let result = auction.send_with_value(
+        buyer,
+        Action::Buy {
+            max_price: 999_000_000,
+        },
+        1_000_000_000,
+    );
+
+    assert_eq!(reply(&result), Err(Error::MaxPriceExceeded));
+    assert_eq!(balance(&auction, buyer), 1_000_000_000);
+}
 
-    assert!(result.contains(&(
+#[test]
+fn price_never_drops_below_min_price() {
+    let sys = System::new();
+
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            min_price: 950_000_000,
+            ..create_config(2, 1_000_000_000)
+        },
+    );
+    sys.spend_blocks(100_000);
+    let result = auction.send_with_value(
         USERS[1],
-        Err::<Event, Error>(Error::InsufficientMoney).encode()
-    )));
+        Action::Buy {
+            max_price: 950_000_000,
+        },
+        950_000_000,
+    );
+
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 950_000_000,
+            ..
+        })
+    ));
 }
 
 #[test]
@@ -121,10 +212,7 @@ fn create_auction_twice_in_a_row() {
     init_nft(&sys, USERS[1]);
     let result = update_auction(&auction, USERS[1], 3, 999_000_000);
 
-    assert!(result.contains(&(
-        USERS[1],
-        Err::<Event, Error>(Error::AlreadyRunning).encode()
-    )));
+    assert_eq!(reply(&result), Err(Error::AlreadyRunning));
 }
 
 #[test]
@@ -136,18 +224,11 @@ fn create_auction_twice_after_time_and_stop() {
     let owner_user = USERS[0];
     init_nft(&sys, USERS[1]);
     let result = update_auction(&auction, USERS[1], 3, 999_000_000);
-    println!("{:?}", result.decoded_log::<Result<Event, Error>>());
+    println!("{:?}", reply(&result));
 
     let result = auction.send(owner_user, Action::ForceStop);
 
-    assert!(result.contains(&(
-        owner_user,
-        Ok::<Event, Error>(Event::AuctionStopped {
-            token_owner: owner_user.into(),
-            token_id: 0.into(),
-        })
-        .encode()
-    )));
+    assert!(matches!(reply(&result), Ok(Event::AuctionStopped { .. })));
 }
 
 #[test]
@@ -158,10 +239,7 @@ fn create_auction_with_low_price() {
     init_nft(&sys, USERS[1]);
     let result = update_auction(&auction, USERS[1], 3, (DURATION / 1000 - 1).into());
 
-    assert!(result.contains(&(
-        USERS[1],
-        Err::<Event, Error>(Error::AlreadyRunning).encode()
-    )));
+    assert_eq!(reply(&result), Err(Error::AlreadyRunning));
 }
 
 #[test]
@@ -172,12 +250,1573 @@ fn create_and_stop() {
 
     let result = auction.send(owner_user, Action::ForceStop);
 
-    assert!(result.contains(&(
-        owner_user,
-        Ok::<Event, Error>(Event::AuctionStopped {
-            token_owner: owner_user.into(),
+    assert!(matches!(reply(&result), Ok(Event::AuctionStopped { .. })));
+}
+
+#[test]
+fn extend_auction() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+
+    let AuctionState {
+        info: AuctionInfo { expires_at, .. },
+        ..
+    } = auction.read_state().expect("Can't get state");
+    let result = auction.send(
+        seller,
+        Action::ExtendAuction {
+            duration: Duration {
+                hours: 1,
+                minutes: 0,
+                seconds: 0,
+                blocks: 0,
+            },
+        },
+    );
+
+    let Ok(Event::AuctionExtended {
+        expires_at: extended_expires_at,
+        ..
+    }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(extended_expires_at, expires_at + 60 * 60 * 1000);
+}
+
+#[test]
+fn extend_auction_with_low_price() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+
+    let result = auction.send(
+        seller,
+        Action::ExtendAuction {
+            duration: Duration {
+                hours: 1_000,
+                minutes: 0,
+                seconds: 0,
+                blocks: 0,
+            },
+        },
+    );
+
+    assert_eq!(reply(&result), Err(Error::StartPriceLessThatMinimal));
+}
+
+#[test]
+fn extend_auction_not_by_seller() {
+    let sys = System::new();
+    let auction = init(&sys);
+
+    let result = auction.send(
+        USERS[1],
+        Action::ExtendAuction {
+            duration: Duration {
+                hours: 1,
+                minutes: 0,
+                seconds: 0,
+                blocks: 0,
+            },
+        },
+    );
+
+    assert_eq!(reply(&result), Err(Error::NotSeller));
+}
+
+#[test]
+fn relist_expired_auction() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let result = auction.send(seller, Action::Relist(create_config(2, 800_000_000)));
+
+    let Ok(Event::AuctionStarted { lot, price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(lot.auction_id, 2);
+    assert_eq!(lot.seller, seller.into());
+    assert_eq!(price, 800_000_000);
+
+    let AuctionState {
+        info: AuctionInfo {
+            status,
+            token_owner,
+            ..
+        },
+        ..
+    } = auction.read_state().expect("Can't get state");
+    assert!(matches!(status, Status::IsRunning));
+    assert_eq!(token_owner, seller.into());
+
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        800_000_000,
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 800_000_000,
+            dust: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn relist_running_auction() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+
+    let result = auction.send(seller, Action::Relist(create_config(2, 800_000_000)));
+
+    assert_eq!(reply(&result), Err(Error::WrongState));
+}
+
+#[test]
+fn reclaim_nft_after_expiration() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    let nft_program = sys.get_program(2);
+    sys.spend_blocks(DURATION);
+
+    let result = auction.send(seller, Action::ReclaimNft);
+
+    let Ok(Event::NftReclaimed { lot, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(lot.seller, seller.into());
+
+    let res = nft_owner(&nft_program, seller, 0.into());
+    let log = Log::builder()
+        .dest(seller)
+        .payload(nft_io::NFTEvent::Owner {
+            owner: seller.into(),
+            token_id: 0.into(),
+        });
+    assert!(res.contains(&log));
+
+    let AuctionState {
+        info: AuctionInfo { status, .. },
+        ..
+    } = auction.read_state().expect("Can't get state");
+    assert!(matches!(status, Status::Reclaimed));
+}
+
+#[test]
+fn reclaim_nft_before_expiration() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+
+    let result = auction.send(seller, Action::ReclaimNft);
+
+    assert_eq!(reply(&result), Err(Error::WrongState));
+}
+
+#[test]
+fn withdraw_overpayment() {
+    let sys = System::new();
+    let buyer = USERS[1];
+    let auction = init(&sys);
+    sys.spend_blocks(100_000);
+
+    let result = auction.send_with_value(
+        buyer,
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 900_000_000,
+            dust: 0,
+            ..
+        })
+    ));
+
+    assert_eq!(balance(&auction, buyer), 100_000_000);
+
+    let result = auction.send(buyer, Action::Withdraw);
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Withdrawn {
+            amount: 100_000_000,
+            ..
+        })
+    ));
+    sys.claim_value_from_mailbox(buyer);
+    assert_eq!(sys.balance_of(buyer), 100_000_000);
+
+    let result = auction.send(buyer, Action::Withdraw);
+    assert_eq!(reply(&result), Err(Error::NothingToWithdraw));
+}
+
+#[test]
+fn failed_buy_is_refundable() {
+    let sys = System::new();
+    let buyer = USERS[1];
+    let auction = init(&sys);
+
+    auction.send_with_value(
+        buyer,
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        999_000_000,
+    );
+
+    assert_eq!(balance(&auction, buyer), 999_000_000);
+}
+
+#[test]
+fn value_of_other_actions_is_refundable() {
+    let sys = System::new();
+    let (seller, user) = (USERS[0], USERS[1]);
+    let auction = init(&sys);
+
+    let result = auction.send_with_value(seller, Action::Reward, 1_000);
+    assert_eq!(reply(&result), Err(Error::WrongState));
+    auction.send_with_value(user, Action::Query(StateQuery::Bids), 2_000);
+    let result = auction.send_with_value(
+        seller,
+        Action::ExtendAuction {
+            duration: Duration {
+                hours: 1,
+                ..Default::default()
+            },
+        },
+        3_000,
+    );
+    assert!(matches!(reply(&result), Ok(Event::AuctionExtended { .. })));
+
+    assert_eq!(balance(&auction, seller), 4_000);
+    assert_eq!(balance(&auction, user), 2_000);
+}
+
+#[test]
+fn dust_threshold_is_bounded() {
+    let sys = System::new();
+    let auction = Program::current(&sys);
+
+    let result = auction.send(
+        USERS[0],
+        InitConfig {
+            dust_threshold: EXISTENTIAL_DEPOSIT + 1,
+            ..Default::default()
+        },
+    );
+    assert!(result.main_failed());
+}
+
+#[test]
+fn dust_goes_to_fee_recipient() {
+    let sys = System::new();
+    let buyer = USERS[1];
+    let fee_recipient = USERS[2];
+    let auction = init_with_config(
+        &sys,
+        InitConfig {
+            dust_destination: DustDestination::FeeRecipient(fee_recipient.into()),
+            ..Default::default()
+        },
+        create_config(2, 1_000_000_000),
+    );
+
+    let result = auction.send_with_value(
+        buyer,
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_100,
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 1_000_000_000,
+            dust: 100,
+            ..
+        })
+    ));
+
+    assert_eq!(balance(&auction, fee_recipient), 100);
+    assert_eq!(balance(&auction, buyer), 0);
+}
+
+#[test]
+fn reward_with_proceeds_splits() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let artist = USERS[2];
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            proceeds_splits: vec![(seller.into(), 7_000), (artist.into(), 3_000)],
+            ..create_config(2, 1_000_000_000)
+        },
+    );
+
+    auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    let result = auction.send(seller, Action::Reward);
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Rewarded {
+            price: 1_000_000_000,
+            ..
+        })
+    ));
+
+    sys.claim_value_from_mailbox(seller);
+    sys.claim_value_from_mailbox(artist);
+    assert_eq!(sys.balance_of(seller), 1_700_000_000);
+    assert_eq!(sys.balance_of(artist), 1_300_000_000);
+}
+
+#[test]
+fn create_auction_with_invalid_proceeds_splits() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let too_many = (0..=MAX_PROCEEDS_SPLITS as u64)
+        .map(|recipient| ((recipient + 10).into(), 0))
+        .chain([(seller.into(), 10_000)])
+        .collect();
+    for proceeds_splits in [
+        vec![(seller.into(), 7_000), (USERS[2].into(), 2_000)],
+        vec![(ActorId::zero(), 10_000)],
+        vec![(seller.into(), 5_000), (seller.into(), 5_000)],
+        too_many,
+    ] {
+        let result = auction.send(
+            seller,
+            Action::Relist(CreateConfig {
+                proceeds_splits,
+                ..create_config(2, 1_000_000_000)
+            }),
+        );
+        assert_eq!(reply(&result), Err(Error::InvalidProceedsSplits));
+    }
+}
+
+#[test]
+fn create_auction_with_invalid_min_price() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let result = auction.send(
+        seller,
+        Action::Relist(CreateConfig {
+            min_price: 1_000_000_001,
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert_eq!(reply(&result), Err(Error::InvalidMinPrice));
+}
+
+#[test]
+fn buy_for_recipient() {
+    let sys = System::new();
+    let payer = USERS[1];
+    let recipient = USERS[2];
+    let auction = init(&sys);
+    let nft_program = sys.get_program(2);
+
+    let result = auction.send_with_value(
+        payer,
+        Action::BuyFor {
+            recipient: recipient.into(),
+            max_price: 1_000_000_000,
+        },
+        1_000_000_500,
+    );
+    let Ok(Event::Bought { buyer, recipient: bought_for, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(buyer, payer.into());
+    assert_eq!(bought_for, recipient.into());
+
+    let res = nft_owner(&nft_program, USERS[0], 0.into());
+    let log = Log::builder()
+        .dest(USERS[0])
+        .payload(nft_io::NFTEvent::Owner {
+            owner: recipient.into(),
+            token_id: 0.into(),
+        });
+    assert!(res.contains(&log));
+
+    assert_eq!(balance(&auction, payer), 500);
+}
+
+fn commit_reveal_config() -> CreateConfig {
+    CreateConfig {
+        commit_reveal: Some(CommitRevealConfig {
+            deposit: 100_000_000,
+            reveal_window: Duration {
+                minutes: 10,
+                ..Default::default()
+            },
+        }),
+        ..create_config(2, 1_000_000_000)
+    }
+}
+
+#[test]
+fn earliest_commit_wins() {
+    let sys = System::new();
+    let (first, second) = (USERS[1], USERS[2]);
+    let salt = [1; 32];
+    let auction = init_with_config(&sys, InitConfig::default(), commit_reveal_config());
+
+    let result = auction.send_with_value(
+        first,
+        Action::CommitPurchase {
+            commitment: commitment(&first.into(), &salt),
+        },
+        100_000_000,
+    );
+    let Ok(Event::PurchaseCommitted { price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    sys.spend_blocks(60);
+    auction.send_with_value(
+        second,
+        Action::CommitPurchase {
+            commitment: commitment(&second.into(), &salt),
+        },
+        100_000_000,
+    );
+
+    let result = auction.send_with_value(second, Action::RevealPurchase { salt }, 900_000_000);
+    assert!(matches!(reply(&result), Ok(Event::PurchaseRevealed { .. })));
+
+    let result = auction.send_with_value(first, Action::RevealPurchase { salt }, 900_000_000);
+    let Ok(Event::Bought { buyer, price: bought_for, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(buyer, first.into());
+    assert_eq!(bought_for, price);
+
+    assert_eq!(
+        query(&auction, StateQuery::Commits),
+        StateReply::Commits(vec![])
+    );
+    assert_eq!(balance(&auction, second), 1_000_000_000);
+}
+
+#[test]
+fn unrevealed_commit_is_forfeited() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let (first, second) = (USERS[1], USERS[2]);
+    let salt = [1; 32];
+    let auction = init_with_config(&sys, InitConfig::default(), commit_reveal_config());
+
+    auction.send_with_value(
+        first,
+        Action::CommitPurchase {
+            commitment: commitment(&first.into(), &salt),
+        },
+        100_000_000,
+    );
+    sys.spend_blocks(300);
+    auction.send_with_value(
+        second,
+        Action::CommitPurchase {
+            commitment: commitment(&second.into(), &salt),
+        },
+        100_000_000,
+    );
+    sys.spend_blocks(400);
+
+    let result = auction.send_with_value(first, Action::RevealPurchase { salt }, 900_000_000);
+    assert_eq!(reply(&result), Err(Error::RevealWindowClosed));
+
+    let result = auction.send_with_value(second, Action::RevealPurchase { salt }, 900_000_000);
+    let Ok(Event::Bought { buyer, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(buyer, second.into());
+
+    assert_eq!(balance(&auction, seller), 100_000_000);
+    assert_eq!(balance(&auction, first), 900_000_000);
+}
+
+#[test]
+fn reveal_with_wrong_salt() {
+    let sys = System::new();
+    let buyer = USERS[1];
+    let auction = init_with_config(&sys, InitConfig::default(), commit_reveal_config());
+
+    auction.send_with_value(
+        buyer,
+        Action::CommitPurchase {
+            commitment: commitment(&buyer.into(), &[1; 32]),
+        },
+        100_000_000,
+    );
+    let result =
+        auction.send_with_value(buyer, Action::RevealPurchase { salt: [2; 32] }, 900_000_000);
+
+    assert_eq!(reply(&result), Err(Error::InvalidReveal));
+    assert_eq!(balance(&auction, buyer), 900_000_000);
+}
+
+#[test]
+fn commit_deposit_is_bounded() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let result = auction.send(
+        seller,
+        Action::Relist(CreateConfig {
+            commit_reveal: Some(CommitRevealConfig {
+                deposit: MIN_OFFER_DEPOSIT - 1,
+                reveal_window: Duration {
+                    minutes: 10,
+                    ..Default::default()
+                },
+            }),
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert_eq!(reply(&result), Err(Error::InvalidCommitDeposit));
+}
+
+#[test]
+fn commits_are_bounded() {
+    let sys = System::new();
+    let salt = [1; 32];
+    let auction = init_with_config(&sys, InitConfig::default(), commit_reveal_config());
+
+    for buyer in 100..=100 + MAX_COMMITS as u64 {
+        sys.mint_to(buyer, 100_000_000);
+        let result = auction.send_with_value(
+            buyer,
+            Action::CommitPurchase {
+                commitment: commitment(&buyer.into(), &salt),
+            },
+            100_000_000,
+        );
+        if buyer < 100 + MAX_COMMITS as u64 {
+            assert!(matches!(
+                reply(&result),
+                Ok(Event::PurchaseCommitted { .. })
+            ));
+        } else {
+            assert_eq!(reply(&result), Err(Error::TooManyCommits));
+            assert_eq!(balance(&auction, buyer), 100_000_000);
+        }
+    }
+}
+
+#[test]
+fn buy_in_commit_reveal_auction() {
+    let sys = System::new();
+    let auction = init_with_config(&sys, InitConfig::default(), commit_reveal_config());
+
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+
+    assert_eq!(reply(&result), Err(Error::CommitRevealRequired));
+}
+
+#[test]
+fn english_auction() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let (first, second) = (USERS[1], USERS[2]);
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            auction_type: AuctionType::English {
+                min_increment: 100_000_000,
+            },
+            discount_rate: 0,
+            ..create_config(2, 200_000_000)
+        },
+    );
+
+    let result = auction.send_with_value(first, Action::Bid, 200_000_000);
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::BidPlaced {
+            amount: 200_000_000,
+            ..
+        })
+    ));
+    let result = auction.send_with_value(second, Action::Bid, 250_000_000);
+    assert_eq!(reply(&result), Err(Error::BidTooLow));
+    auction.send_with_value(second, Action::Bid, 300_000_000);
+
+    assert_eq!(balance(&auction, first), 200_000_000);
+    assert_eq!(balance(&auction, second), 250_000_000);
+
+    sys.spend_blocks(DURATION);
+    let result = auction.send(seller, Action::ReclaimNft);
+    assert_eq!(reply(&result), Err(Error::BidsPending));
+
+    let result = auction.send(first, Action::Settle);
+    let Ok(Event::Bought { buyer, price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(buyer, second.into());
+    assert_eq!(price, 300_000_000);
+}
+
+#[test]
+fn sealed_bid_auction() {
+    let sys = System::new();
+    let (first, second) = (USERS[1], USERS[2]);
+    let salt = [1; 32];
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            auction_type: AuctionType::SealedBid {
+                reveal_window: Duration {
+                    minutes: 10,
+                    ..Default::default()
+                },
+            },
+            discount_rate: 0,
+            ..create_config(2, 100_000_000)
+        },
+    );
+
+    auction.send_with_value(
+        first,
+        Action::SealBid {
+            commitment: bid_commitment(&first.into(), 700_000_000, &salt),
+        },
+        800_000_000,
+    );
+    auction.send_with_value(
+        second,
+        Action::SealBid {
+            commitment: bid_commitment(&second.into(), 500_000_000, &salt),
+        },
+        500_000_000,
+    );
+    let result = auction.send(
+        first,
+        Action::RevealBid {
+            amount: 700_000_000,
+            salt,
+        },
+    );
+    assert_eq!(reply(&result), Err(Error::WrongState));
+
+    sys.spend_blocks(168 * 60 * 60 + 1);
+    for (bidder, amount) in [(first, 700_000_000), (second, 500_000_000)] {
+        let result = auction.send(bidder, Action::RevealBid { amount, salt });
+        assert!(matches!(reply(&result), Ok(Event::BidRevealed { .. })));
+    }
+    let result = auction.send(first, Action::Settle);
+    assert_eq!(reply(&result), Err(Error::WrongState));
+
+    sys.spend_blocks(600);
+    let result = auction.send(first, Action::Settle);
+    let Ok(Event::Bought { buyer, price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(buyer, first.into());
+    assert_eq!(price, 500_000_000);
+
+    assert_eq!(balance(&auction, first), 300_000_000);
+    assert_eq!(balance(&auction, second), 500_000_000);
+}
+
+#[test]
+fn late_bid_extends_auction() {
+    let sys = System::new();
+    let (first, second) = (USERS[1], USERS[2]);
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            auction_type: AuctionType::English {
+                min_increment: 100_000_000,
+            },
+            anti_sniping: Some(AntiSnipingConfig {
+                threshold: Duration {
+                    minutes: 10,
+                    ..Default::default()
+                },
+                extension: Duration {
+                    minutes: 10,
+                    ..Default::default()
+                },
+            }),
+            discount_rate: 0,
+            ..create_config(2, 500_000_000)
+        },
+    );
+    let AuctionState {
+        info: AuctionInfo { expires_at, .. },
+        ..
+    } = auction.read_state().expect("Can't get state");
+
+    sys.spend_blocks(168 * 60 * 60 - 300);
+    let result = auction.send_with_value(first, Action::Bid, 500_000_000);
+    let Ok(Event::BidPlaced {
+        expires_at: new_expires_at,
+        extended: true,
+        ..
+    }) = reply(&result)
+    else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(new_expires_at, expires_at + 600_000);
+
+    sys.spend_blocks(400);
+    let result = auction.send_with_value(second, Action::Bid, 600_000_000);
+    assert!(matches!(reply(&result), Ok(Event::BidPlaced { .. })));
+}
+
+#[test]
+fn late_bid_extension_is_capped() {
+    let sys = System::new();
+    let (first, second) = (USERS[1], USERS[2]);
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            auction_type: AuctionType::English {
+                min_increment: 100_000_000,
+            },
+            anti_sniping: Some(AntiSnipingConfig {
+                threshold: Duration {
+                    hours: 365 * 24,
+                    ..Default::default()
+                },
+                extension: Duration {
+                    minutes: 10,
+                    ..Default::default()
+                },
+            }),
+            discount_rate: 0,
+            duration: Duration {
+                hours: 365 * 24 - 1,
+                minutes: 55,
+                ..Default::default()
+            },
+            ..create_config(2, 500_000_000)
+        },
+    );
+    let AuctionState {
+        info: AuctionInfo { expires_at, .. },
+        ..
+    } = auction.read_state().expect("Can't get state");
+
+    let result = auction.send_with_value(first, Action::Bid, 500_000_000);
+    let Ok(Event::BidPlaced {
+        expires_at: capped,
+        extended: true,
+        ..
+    }) = reply(&result)
+    else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(capped, expires_at + 300_000);
+
+    let result = auction.send_with_value(second, Action::Bid, 600_000_000);
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::BidPlaced { expires_at, extended: false, .. }) if expires_at == capped
+    ));
+}
+
+fn reverse_config() -> ReverseConfig {
+    ReverseConfig {
+        starting_price: 100_000_000,
+        increase_rate: 1_000,
+        duration: Duration {
+            hours: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reverse_auction() {
+    let sys = System::new();
+    let (creator, provider) = (USERS[1], USERS[2]);
+    let auction = init_program(&sys, InitConfig::default());
+
+    let result = auction.send_with_value(
+        creator,
+        Action::CreateReverse(reverse_config()),
+        500_000_000,
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::AuctionStarted {
+            price: 100_000_000,
+            ..
+        })
+    ));
+
+    sys.spend_blocks(1_000);
+    let result = auction.send(provider, Action::Accept);
+    let Ok(Event::Accepted { provider: accepted_by, price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(accepted_by, provider.into());
+    assert!(price >= 101_000_000);
+
+    sys.claim_value_from_mailbox(provider);
+    assert_eq!(sys.balance_of(provider), 1_000_000_000 + price);
+    assert_eq!(balance(&auction, creator), (500_000_000 - price));
+}
+
+#[test]
+fn restart_requires_finished_auction() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let result = auction.send(seller, Action::Create(create_config(2, 1_000_000_000)));
+    assert_eq!(reply(&result), Err(Error::WrongState));
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::CreateReverse(reverse_config()),
+        500_000_000,
+    );
+    assert_eq!(reply(&result), Err(Error::WrongState));
+    assert_eq!(balance(&auction, USERS[1]), 500_000_000);
+
+    auction.send(seller, Action::ReclaimNft);
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::CreateReverse(reverse_config()),
+        500_000_000,
+    );
+    assert!(matches!(reply(&result), Ok(Event::AuctionStarted { .. })));
+}
+
+#[test]
+fn purchased_auction_is_not_replaced() {
+    let sys = System::new();
+    let auction = init(&sys);
+    auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+
+    let result = auction.send_with_value(
+        USERS[2],
+        Action::CreateReverse(reverse_config()),
+        500_000_000,
+    );
+    assert_eq!(reply(&result), Err(Error::WrongState));
+}
+
+#[test]
+fn reclaim_budget_after_expiration() {
+    let sys = System::new();
+    let creator = USERS[1];
+    let auction = init_program(&sys, InitConfig::default());
+
+    auction.send_with_value(
+        creator,
+        Action::CreateReverse(reverse_config()),
+        500_000_000,
+    );
+    sys.spend_blocks(60 * 60);
+
+    let result = auction.send(USERS[2], Action::Accept);
+    assert_eq!(reply(&result), Err(Error::AlreadyStopped));
+    let result = auction.send(creator, Action::ReclaimBudget);
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::BudgetReclaimed {
+            amount: 500_000_000,
+            ..
+        })
+    ));
+    sys.claim_value_from_mailbox(creator);
+    assert_eq!(sys.balance_of(creator), 1_000_000_000);
+}
+
+#[test]
+fn gradual_auction() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let (first, second) = (USERS[1], USERS[2]);
+    let auction = init_program(&sys, InitConfig::default());
+    init_nft(&sys, seller);
+    let nft_program = sys.get_program(2);
+    mint_approved(&nft_program, seller, 1, 2);
+
+    let result = auction.send(
+        seller,
+        Action::Create(CreateConfig {
+            auction_type: AuctionType::Gradual {
+                token_ids: vec![1.into()],
+                price_bump: 1_000,
+            },
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert!(matches!(reply(&result), Ok(Event::AuctionStarted { .. })));
+
+    let result = auction.send_with_value(
+        first,
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    let Ok(Event::Bought { price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    sys.claim_value_from_mailbox(seller);
+    assert_eq!(sys.balance_of(seller), 1_000_000_000 + price);
+
+    let AuctionState {
+        info:
+            AuctionInfo {
+                status,
+                token_id,
+                starting_price,
+                queued_tokens,
+                ..
+            },
+        ..
+    } = auction.read_state().expect("Can't get state");
+    assert!(matches!(status, Status::IsRunning));
+    assert_eq!(token_id, 1.into());
+    assert_eq!(starting_price, price * 11 / 10);
+    assert!(queued_tokens.is_empty());
+
+    sys.spend_blocks(200_000);
+    let result = auction.send_with_value(
+        second,
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    assert!(matches!(reply(&result), Ok(Event::Bought { .. })));
+
+    let AuctionState {
+        info: AuctionInfo { status, .. },
+        ..
+    } = auction.read_state().expect("Can't get state");
+    assert!(matches!(status, Status::Rewarded { .. }));
+    let res = nft_owner(&nft_program, seller, 1.into());
+    let log = Log::builder()
+        .dest(seller)
+        .payload(nft_io::NFTEvent::Owner {
+            owner: second.into(),
+            token_id: 1.into(),
+        });
+    assert!(res.contains(&log));
+}
+
+#[test]
+fn failed_transfer_is_not_rewarded() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let buyer = USERS[1];
+    let auction = init_program(&sys, InitConfig::default());
+    init_nft(&sys, seller);
+    let nft_program = sys.get_program(2);
+    mint_approved(&nft_program, seller, 1, 2);
+
+    let result = auction.send(
+        seller,
+        Action::Create(CreateConfig {
+            auction_type: AuctionType::Gradual {
+                token_ids: vec![1.into()],
+                price_bump: 1_000,
+            },
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert!(matches!(reply(&result), Ok(Event::AuctionStarted { .. })));
+
+    // The seller keeps the NFT of a gradual auction and can move it away
+    let res = nft_program.send(
+        seller,
+        nft_io::NFTAction::Transfer {
+            transaction_id: 4,
+            to: USERS[2].into(),
+            token_id: 0.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let result = auction.send_with_value(
+        buyer,
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    assert_eq!(reply(&result), Err(Error::NftTransferFailed));
+
+    let result = auction.send(seller, Action::Reward);
+    assert_eq!(reply(&result), Err(Error::WrongState));
+
+    let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+    assert!(matches!(info.status, Status::IsRunning));
+    assert_eq!(balance(&auction, buyer), 1_000_000_000);
+    assert_eq!(balance(&auction, seller), 0);
+}
+
+#[test]
+fn offer_fills_when_price_drops() {
+    let sys = System::new();
+    let (offerer, other) = (USERS[1], USERS[2]);
+    let auction = init(&sys);
+    let nft_program = sys.get_program(2);
+
+    let result = auction.send_with_value(
+        offerer,
+        Action::PlaceOffer { price: 900_000_000 },
+        900_000_000,
+    );
+    assert!(matches!(reply(&result), Ok(Event::OfferPlaced { .. })));
+    let result = auction.send(other, Action::FillOffer);
+    assert_eq!(reply(&result), Err(Error::NoFillableOffer));
+
+    sys.spend_blocks(100_000);
+    let result = auction.send_with_value(
+        other,
+        Action::PlaceOffer { price: 800_000_000 },
+        800_000_000,
+    );
+    assert!(matches!(reply(&result), Ok(Event::OfferPlaced { .. })));
+    let result = auction.send_with_value(
+        other,
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        100_000_000,
+    );
+    assert_eq!(reply(&result), Err(Error::OfferFilled));
+    let history = history(&auction, 0, 1, HistoryFilter::All);
+    assert_eq!(history[0].buyer, Some(offerer.into()));
+
+    let AuctionState {
+        info: AuctionInfo { status, .. },
+        ..
+    } = auction.read_state().expect("Can't get state");
+    assert!(matches!(status, Status::Purchased { .. }));
+    assert_eq!(
+        query(&auction, StateQuery::Offers),
+        StateReply::Offers(vec![])
+    );
+    assert_eq!(balance(&auction, other), 900_000_000);
+
+    let res = nft_owner(&nft_program, USERS[0], 0.into());
+    let log = Log::builder()
+        .dest(USERS[0])
+        .payload(nft_io::NFTEvent::Owner {
+            owner: offerer.into(),
+            token_id: 0.into(),
+        });
+    assert!(res.contains(&log));
+}
+
+#[test]
+fn offers_without_deposit() {
+    let sys = System::new();
+    let offerer = USERS[1];
+    let auction = init(&sys);
+
+    let result = auction.send(offerer, Action::PlaceOffer { price: 0 });
+    assert_eq!(reply(&result), Err(Error::InsufficientMoney));
+
+    sys.spend_blocks(DURATION);
+    let result = auction.send(offerer, make_offer());
+    assert_eq!(reply(&result), Err(Error::InsufficientMoney));
+    assert_eq!(
+        query(&auction, StateQuery::Offers),
+        StateReply::Offers(vec![])
+    );
+}
+
+fn make_offer() -> Action {
+    Action::MakeOffer {
+        duration: Duration {
+            minutes: 1,
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn accept_offer_after_expiration() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let offerer = USERS[1];
+    let auction = init(&sys);
+    let nft_program = sys.get_program(2);
+
+    let result = auction.send(offerer, make_offer());
+    assert_eq!(reply(&result), Err(Error::WrongState));
+
+    sys.spend_blocks(DURATION);
+    auction.send_with_value(offerer, make_offer(), 700_000_000);
+    let result = auction.send(
+        seller,
+        Action::AcceptOffer {
+            offerer: offerer.into(),
+        },
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 700_000_000,
+            ..
+        })
+    ));
+
+    let res = nft_owner(&nft_program, seller, 0.into());
+    let log = Log::builder()
+        .dest(seller)
+        .payload(nft_io::NFTEvent::Owner {
+            owner: offerer.into(),
+            token_id: 0.into(),
+        });
+    assert!(res.contains(&log));
+}
+
+#[test]
+fn accept_offer_after_handing_nft_on() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let (offerer, new_owner) = (USERS[1], USERS[2]);
+    let auction = init(&sys);
+    let nft_program = sys.get_program(2);
+    sys.spend_blocks(DURATION);
+    auction.send(seller, Action::ReclaimNft);
+    auction.send_with_value(offerer, make_offer(), 700_000_000);
+
+    let res = nft_program.send(
+        seller,
+        nft_io::NFTAction::Transfer {
+            transaction_id: 2,
+            to: new_owner.into(),
+            token_id: 0.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = nft_program.send(
+        new_owner,
+        nft_io::NFTAction::Approve {
+            to: 1.into(),
             token_id: 0.into(),
+            transaction_id: 0,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let result = auction.send(
+        seller,
+        Action::AcceptOffer {
+            offerer: offerer.into(),
+        },
+    );
+    assert_eq!(reply(&result), Err(Error::NotSeller));
+
+    let res = nft_owner(&nft_program, seller, 0.into());
+    let log = Log::builder()
+        .dest(seller)
+        .payload(nft_io::NFTEvent::Owner {
+            owner: new_owner.into(),
+            token_id: 0.into(),
+        });
+    assert!(res.contains(&log));
+    assert_eq!(balance(&auction, offerer), 0);
+}
+
+#[test]
+fn invalid_create_keeps_offers() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let offerer = USERS[1];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+    auction.send(seller, Action::ReclaimNft);
+    auction.send_with_value(offerer, make_offer(), 700_000_000);
+
+    let result = auction.send(seller, Action::Create(create_config(2, 0)));
+    assert_eq!(reply(&result), Err(Error::ZeroStartingPrice));
+
+    let StateReply::Offers(offers) = query(&auction, StateQuery::Offers) else {
+        panic!("Unexpected query reply");
+    };
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0].0, offerer.into());
+    assert_eq!(balance(&auction, offerer), 0);
+}
+
+#[test]
+fn reject_and_withdraw_offers() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let (first, second) = (USERS[1], USERS[2]);
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    auction.send_with_value(first, make_offer(), 700_000_000);
+    auction.send_with_value(second, make_offer(), 600_000_000);
+    let result = auction.send(
+        seller,
+        Action::RejectOffer {
+            offerer: second.into(),
+        },
+    );
+    assert!(matches!(reply(&result), Ok(Event::OfferRejected { .. })));
+
+    sys.spend_blocks(60);
+    let result = auction.send(
+        seller,
+        Action::AcceptOffer {
+            offerer: first.into(),
+        },
+    );
+    assert_eq!(reply(&result), Err(Error::OfferExpired));
+
+    let result = auction.send(first, Action::WithdrawOffer);
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::OfferWithdrawn {
+            amount: 700_000_000,
+            ..
+        })
+    ));
+    sys.claim_value_from_mailbox(first);
+    assert_eq!(sys.balance_of(first), 1_000_000_000);
+
+    assert_eq!(
+        query(&auction, StateQuery::Offers),
+        StateReply::Offers(vec![])
+    );
+    assert_eq!(balance(&auction, second), 600_000_000);
+}
+
+#[test]
+fn buy_now() {
+    let sys = System::new();
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            buy_now_price: Some(800_000_000),
+            ..create_config(2, 1_000_000_000)
+        },
+    );
+    let AuctionState {
+        info:
+            AuctionInfo {
+                buy_now_price,
+                current_price,
+                ..
+            },
+        ..
+    } = auction.read_state().expect("Can't get state");
+    assert_eq!(buy_now_price, Some(800_000_000));
+    assert_eq!(current_price, 800_000_000);
+
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 800_000_000,
+        },
+        800_000_000,
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 800_000_000,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn create_auction_with_invalid_buy_now_price() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    for (buy_now_price, min_price) in [(1_000_000_001, 0), (0, 0), (400_000_000, 500_000_000)] {
+        let result = auction.send(
+            seller,
+            Action::Relist(CreateConfig {
+                buy_now_price: Some(buy_now_price),
+                min_price,
+                ..create_config(2, 1_000_000_000)
+            }),
+        );
+        assert_eq!(reply(&result), Err(Error::InvalidBuyNowPrice));
+    }
+}
+
+#[test]
+fn price_decreases_every_block() {
+    let sys = System::new();
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            discount_rate: 1_000_000,
+            duration: Duration {
+                blocks: 100,
+                ..Default::default()
+            },
+            decay_unit: DecayUnit::Block,
+            ..create_config(2, 1_000_000_000)
+        },
+    );
+    let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+    assert_eq!(info.decay_unit, DecayUnit::Block);
+    assert_eq!(info.expires_at - info.started_at, 100);
+    assert_eq!(info.price_at(info.started_at + 10), 990_000_000);
+
+    sys.spend_blocks(10);
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 990_000_000,
+        },
+        990_000_000,
+    );
+    let Ok(Event::Bought { price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert!(price <= 990_000_000);
+}
+
+#[test]
+fn create_auction_with_invalid_duration() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let result = auction.send(
+        seller,
+        Action::Relist(CreateConfig {
+            duration: Duration {
+                seconds: 60,
+                blocks: 10,
+                ..Default::default()
+            },
+            decay_unit: DecayUnit::Block,
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert_eq!(reply(&result), Err(Error::InvalidDuration));
+
+    let result = auction.send(
+        seller,
+        Action::Relist(CreateConfig {
+            duration: Duration {
+                blocks: 10,
+                ..Default::default()
+            },
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert_eq!(reply(&result), Err(Error::InvalidDuration));
+}
+
+#[test]
+fn create_auction_with_extreme_config() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let result = auction.send(
+        seller,
+        Action::Relist(CreateConfig {
+            starting_price: u128::MAX,
+            discount_rate: u128::MAX,
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert_eq!(reply(&result), Err(Error::PriceOverflow));
+
+    let result = auction.send(
+        seller,
+        Action::Relist(CreateConfig {
+            duration: Duration {
+                hours: u64::MAX,
+                ..Default::default()
+            },
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert_eq!(reply(&result), Err(Error::InvalidDuration));
+
+    let result = auction.send(
+        seller,
+        Action::Relist(CreateConfig {
+            starting_price: u128::MAX,
+            discount_rate: u128::MAX / 1_000_000,
+            ..create_config(2, 1_000_000_000)
+        }),
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::AuctionStarted {
+            price: u128::MAX,
+            ..
+        })
+    ));
+
+    sys.spend_blocks(DURATION / 2);
+    let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+    assert!(info.current_price < u128::MAX);
+}
+
+#[test]
+fn create_auction_with_invalid_config() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init_program(&sys, InitConfig::default());
+
+    let cases = [
+        (create_config(0, 1_000_000_000), Error::InvalidNftContract),
+        (create_config(1, 1_000_000_000), Error::InvalidNftContract),
+        (create_config(2, 0), Error::ZeroStartingPrice),
+        (
+            CreateConfig {
+                discount_rate: 0,
+                ..create_config(2, 1_000_000_000)
+            },
+            Error::ZeroDiscountRate,
+        ),
+        (
+            CreateConfig {
+                fixed_price: true,
+                ..create_config(2, 1_000_000_000)
+            },
+            Error::FixedPriceWithDiscount,
+        ),
+        (
+            CreateConfig {
+                duration: Duration::default(),
+                ..create_config(2, 1_000_000_000)
+            },
+            Error::ZeroDuration,
+        ),
+        (
+            CreateConfig {
+                duration: Duration {
+                    hours: 366 * 24,
+                    ..Default::default()
+                },
+                ..create_config(2, 1_000_000_000)
+            },
+            Error::DurationTooLong,
+        ),
+        (
+            CreateConfig {
+                auction_type: AuctionType::English { min_increment: 0 },
+                discount_rate: 0,
+                ..commit_reveal_config()
+            },
+            Error::WrongAuctionType,
+        ),
+    ];
+    for (config, error) in cases {
+        let result = auction.send(seller, Action::Create(config));
+        assert_eq!(reply(&result), Err(error));
+    }
+}
+
+#[test]
+fn gradual_auction_with_repeated_tokens() {
+    let sys = System::new();
+    let seller = USERS[0];
+    let auction = init_program(&sys, InitConfig::default());
+    init_nft(&sys, seller);
+    let nft_program = sys.get_program(2);
+    mint_approved(&nft_program, seller, 1, 2);
+
+    for token_ids in [vec![1.into(), 1.into()], vec![0.into(), 1.into()]] {
+        let result = auction.send(
+            seller,
+            Action::Create(CreateConfig {
+                auction_type: AuctionType::Gradual {
+                    token_ids,
+                    price_bump: 1_000,
+                },
+                ..create_config(2, 1_000_000_000)
+            }),
+        );
+        assert_eq!(reply(&result), Err(Error::WrongToken));
+    }
+}
+
+#[test]
+fn fixed_price_auction() {
+    let sys = System::new();
+    let auction = init_with_config(
+        &sys,
+        InitConfig::default(),
+        CreateConfig {
+            discount_rate: 0,
+            fixed_price: true,
+            ..create_config(2, 1_000_000_000)
+        },
+    );
+    sys.spend_blocks(DURATION / 2);
+
+    let result = auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+    assert!(matches!(
+        reply(&result),
+        Ok(Event::Bought {
+            price: 1_000_000_000,
+            ..
         })
-        .encode()
-    )));
+    ));
 }
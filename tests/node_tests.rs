@@ -75,9 +75,9 @@ async fn create_and_stop() -> Result<()> {
     assert!(listener.message_processed(message_id).await?.succeed());
 
     // Upload And Init Auction
-    let payload: Vec<u8> = vec![];
+    let payload = InitConfig::default().encode();
     let gas_info = api
-        .calculate_upload_gas(None, WASM_BINARY_OPT.into(), vec![], 0, true)
+        .calculate_upload_gas(None, WASM_BINARY_OPT.into(), payload.clone(), 0, true)
         .await?;
     let (message_id, auction_program_id, _hash) = api
         .upload_program(
@@ -124,7 +124,9 @@ async fn create_and_stop() -> Result<()> {
             hours: 0,
             minutes: 5,
             seconds: 0,
+            blocks: 0,
         },
+        ..Default::default()
     });
     let gas_info = api
         .calculate_handle_gas(None, auction_program_id, create.encode(), 0, true)
@@ -138,12 +140,12 @@ async fn create_and_stop() -> Result<()> {
 
     std::thread::sleep(std::time::Duration::from_secs(10));
 
-    let state: AuctionInfo = api.read_state(auction_program_id).await?;
-    assert!(matches!(state.status, Status::IsRunning));
+    let state: AuctionState = api.read_state(auction_program_id).await?;
+    assert!(matches!(state.info.status, Status::IsRunning));
 
     // Buy
-    let buy = Action::Buy;
     let value = 1_000_000_000;
+    let buy = Action::Buy { max_price: value };
 
     let (message_id, _) = api
         .send_message(auction_program_id, buy, 250_000_000_000, value)
@@ -152,8 +154,8 @@ async fn create_and_stop() -> Result<()> {
     assert!(listener.message_processed(message_id).await?.succeed());
     assert!(listener.blocks_running().await?);
 
-    let state: AuctionInfo = api.read_state(auction_program_id).await?;
-    assert!(matches!(state.status, Status::Purchased { price: _ }));
+    let state: AuctionState = api.read_state(auction_program_id).await?;
+    assert!(matches!(state.info.status, Status::Purchased { price: _ }));
 
     // ForceStop
     let force_stop = Action::ForceStop;
@@ -167,8 +169,8 @@ async fn create_and_stop() -> Result<()> {
     assert!(listener.message_processed(message_id).await?.succeed());
     assert!(listener.blocks_running().await?);
 
-    let state: AuctionInfo = api.read_state(auction_program_id).await?;
-    assert!(matches!(state.status, Status::Purchased { price: _ }));
+    let state: AuctionState = api.read_state(auction_program_id).await?;
+    assert!(matches!(state.info.status, Status::Purchased { price: _ }));
 
     Ok(())
 }
@@ -236,9 +238,9 @@ async fn create_buy_reward() -> Result<()> {
     assert!(listener.message_processed(message_id).await?.succeed());
 
     // Upload And Init Auction
-    let payload: Vec<u8> = vec![];
+    let payload = InitConfig::default().encode();
     let gas_info = api
-        .calculate_upload_gas(None, WASM_BINARY_OPT.into(), vec![], 0, true)
+        .calculate_upload_gas(None, WASM_BINARY_OPT.into(), payload.clone(), 0, true)
         .await?;
     let (message_id, auction_program_id, _hash) = api
         .upload_program(
@@ -284,7 +286,9 @@ async fn create_buy_reward() -> Result<()> {
             hours: 0,
             minutes: 5,
             seconds: 0,
+            blocks: 0,
         },
+        ..Default::default()
     });
     let gas_info = api
         .calculate_handle_gas(None, auction_program_id, create.encode(), 0, true)
@@ -298,13 +302,13 @@ async fn create_buy_reward() -> Result<()> {
 
     std::thread::sleep(std::time::Duration::from_secs(10));
 
-    let state: AuctionInfo = api.read_state(auction_program_id).await?;
+    let state: AuctionState = api.read_state(auction_program_id).await?;
     dbg!(state);
 
     // Buy
-    let buy = Action::Buy;
-    let buy_payload = buy.encode();
     let value = 1_000_000_000;
+    let buy = Action::Buy { max_price: value };
+    let buy_payload = buy.encode();
     let gas_info = api
         .calculate_handle_gas(None, auction_program_id, buy_payload, value, true)
         .await?;
@@ -315,8 +319,8 @@ async fn create_buy_reward() -> Result<()> {
     assert!(listener.message_processed(message_id).await?.succeed());
     assert!(listener.blocks_running().await?);
 
-    let state: AuctionInfo = api.read_state(auction_program_id).await?;
-    assert!(matches!(state.status, Status::Purchased { price: _ }));
+    let state: AuctionState = api.read_state(auction_program_id).await?;
+    assert!(matches!(state.info.status, Status::Purchased { price: _ }));
 
     // Reward
     let reward = Action::Reward;
@@ -331,8 +335,8 @@ async fn create_buy_reward() -> Result<()> {
     assert!(listener.message_processed(message_id).await?.succeed());
     assert!(listener.blocks_running().await?);
 
-    let state: AuctionInfo = api.read_state(auction_program_id).await?;
-    assert!(matches!(state.status, Status::Rewarded { price: _ }));
+    let state: AuctionState = api.read_state(auction_program_id).await?;
+    assert!(matches!(state.info.status, Status::Rewarded { price: _ }));
 
     Ok(())
 }
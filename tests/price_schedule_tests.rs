@@ -0,0 +1,125 @@
+use auction_io::auction::{DecayUnit, Duration, PriceSchedule, MAX_PRICE_POINTS};
+use proptest::prelude::*;
+
+fn decay_unit() -> impl Strategy<Value = DecayUnit> {
+    prop_oneof![
+        Just(DecayUnit::Second),
+        Just(DecayUnit::Millisecond),
+        Just(DecayUnit::Block),
+    ]
+}
+
+prop_compose! {
+    fn schedule()(
+        starting_price in any::<u128>(),
+        discount_rate in any::<u128>(),
+        min_price in any::<u128>(),
+        started_at in any::<u64>(),
+        decay_unit in decay_unit(),
+        buy_now_price in any::<Option<u128>>(),
+    ) -> PriceSchedule {
+        PriceSchedule {
+            starting_price,
+            discount_rate,
+            min_price,
+            started_at,
+            decay_unit,
+            buy_now_price,
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn price_stays_within_bounds(schedule in schedule(), timestamp in any::<u64>()) {
+        let price = schedule.price_at(timestamp);
+        let cap = schedule.buy_now_price.unwrap_or(u128::MAX);
+        prop_assert!(price >= schedule.min_price.min(cap));
+        prop_assert!(price <= schedule.starting_price.max(schedule.min_price).min(cap));
+    }
+
+    #[test]
+    fn price_never_increases(schedule in schedule(), a in any::<u64>(), b in any::<u64>()) {
+        let (earlier, later) = (a.min(b), a.max(b));
+        prop_assert!(schedule.price_at(later) <= schedule.price_at(earlier));
+    }
+
+    #[test]
+    fn offer_never_exceeds_budget(
+        schedule in schedule(),
+        budget in any::<u128>(),
+        a in any::<u64>(),
+        b in any::<u64>(),
+    ) {
+        let (earlier, later) = (a.min(b), a.max(b));
+        let offer = schedule.offer_at(later, budget);
+        prop_assert!(offer <= budget);
+        prop_assert!(offer >= schedule.offer_at(earlier, budget));
+    }
+
+    #[test]
+    fn price_is_reached_at_computed_time(schedule in schedule(), target in any::<u128>()) {
+        let reached = schedule.reaches_price_at(target);
+        let below_start = target < schedule.starting_price
+            && target < schedule.price_at(schedule.started_at);
+        if let (Some(timestamp), true) = (reached, below_start) {
+            prop_assert!(timestamp > schedule.started_at);
+            prop_assert!(schedule.price_at(timestamp) <= target);
+            prop_assert!(schedule.price_at(timestamp - 1) > target);
+        }
+    }
+
+    #[test]
+    fn points_are_capped(
+        schedule in schedule(),
+        from in any::<u64>(),
+        span in any::<u64>(),
+        interval in 1..u64::MAX,
+    ) {
+        let until = from.saturating_add(span);
+        let points = schedule.points(from, until, interval);
+        prop_assert!(points.len() as u64 <= MAX_PRICE_POINTS);
+        prop_assert_eq!(points.first().map(|(timestamp, _)| *timestamp), Some(from));
+        prop_assert_eq!(points.last().map(|(timestamp, _)| *timestamp), Some(until));
+    }
+
+    #[test]
+    fn price_is_capped_by_buy_now(schedule in schedule(), timestamp in any::<u64>()) {
+        if let Some(buy_now_price) = schedule.buy_now_price {
+            prop_assert!(schedule.price_at(timestamp) <= buy_now_price);
+        }
+    }
+
+    #[test]
+    fn bumped_price_saturates(
+        schedule in schedule(),
+        price in any::<u128>(),
+        timestamp in any::<u64>(),
+        price_bump in any::<u16>(),
+    ) {
+        let next = schedule.after_sale(price, timestamp, price_bump);
+        prop_assert!(next.starting_price >= price);
+        prop_assert_eq!(next.started_at, timestamp);
+    }
+
+    #[test]
+    fn duration_is_checked(
+        hours in any::<u64>(),
+        minutes in any::<u64>(),
+        seconds in any::<u64>(),
+        blocks in any::<u64>(),
+        decay_unit in decay_unit(),
+    ) {
+        let duration = Duration { hours, minutes, seconds, blocks };
+        let millis = ((u128::from(hours) * 60 + u128::from(minutes)) * 60 + u128::from(seconds))
+            * 1000;
+        let expected = match decay_unit {
+            DecayUnit::Block => (millis == 0).then_some(u128::from(blocks)),
+            _ => (blocks == 0).then_some(millis),
+        };
+        prop_assert_eq!(
+            decay_unit.duration(&duration),
+            expected.and_then(|duration| u64::try_from(duration).ok())
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use auction_io::auction::{
+    Action, AuctionInfo, AuctionState, CreateConfig, DecayUnit, Duration, Event, Status,
+};
+use gtest::System;
+use proptest::prelude::*;
+mod routines;
+use routines::*;
+
+fn decay_unit() -> impl Strategy<Value = DecayUnit> {
+    prop_oneof![
+        Just(DecayUnit::Second),
+        Just(DecayUnit::Millisecond),
+        Just(DecayUnit::Block),
+    ]
+}
+
+fn amount() -> impl Strategy<Value = u128> {
+    prop_oneof![Just(0), 1..1_000_000_000_000u128, Just(u128::MAX)]
+}
+
+fn duration() -> impl Strategy<Value = Duration> {
+    (
+        0..10_000u64,
+        0..120u64,
+        0..120u64,
+        prop_oneof![Just(0), 1..100_000u64],
+    )
+        .prop_map(|(hours, minutes, seconds, blocks)| Duration {
+            hours,
+            minutes,
+            seconds,
+            blocks,
+        })
+}
+
+prop_compose! {
+    fn relist_config()(
+        starting_price in amount(),
+        discount_rate in amount(),
+        min_price in amount(),
+        buy_now_price in proptest::option::of(amount()),
+        fixed_price in any::<bool>(),
+        duration in duration(),
+        decay_unit in decay_unit(),
+    ) -> CreateConfig {
+        CreateConfig {
+            starting_price,
+            discount_rate,
+            min_price,
+            buy_now_price,
+            fixed_price,
+            duration,
+            decay_unit,
+            ..create_config(2, 1_000_000_000)
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn relist_is_validated(config in relist_config()) {
+        let sys = System::new();
+        let auction = init(&sys);
+        sys.spend_blocks(DURATION);
+
+        let result = auction.send(USERS[0], Action::Relist(config.clone()));
+        let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+        match reply(&result) {
+            Ok(Event::AuctionStarted { price, expires_at, .. }) => {
+                let AuctionInfo { auction_id, status, started_at, current_price, .. } = info;
+                prop_assert_eq!(auction_id, 2);
+                prop_assert!(matches!(status, Status::IsRunning));
+                prop_assert_eq!(price, config.starting_price);
+                prop_assert!(current_price <= config.starting_price);
+                let cap = config.buy_now_price.unwrap_or(u128::MAX);
+                prop_assert!(current_price >= config.min_price.min(cap));
+                let duration = config.decay_unit.duration(&config.duration);
+                prop_assert_eq!(Some(expires_at - started_at), duration);
+                prop_assert!(expires_at - started_at <= config.decay_unit.max_duration());
+            }
+            Ok(event) => prop_assert!(false, "Unexpected reply: {:?}", event),
+            Err(_) => {
+                prop_assert_eq!(info.auction_id, 1);
+                prop_assert!(matches!(info.status, Status::Expired));
+            }
+        }
+    }
+}
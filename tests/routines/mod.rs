@@ -1,9 +1,12 @@
-use auction_io::auction::{Action, CreateConfig, Duration, Error, Event};
+use auction_io::auction::{
+    Action, AuctionRecord, AuctionState, CreateConfig, Duration, Error, Event, HistoryFilter,
+    InitConfig, StateQuery, StateReply, VersionedEvent,
+};
 use gear_lib::non_fungible_token::{
     io::NFTApproval,
     token::{TokenId, TokenMetadata},
 };
-use gstd::Encode;
+use gstd::Decode;
 use gtest::{Log, Program, RunResult, System};
 use nft_io::{Constraints, InitNFT, NFTEvent};
 
@@ -12,34 +15,38 @@ pub const USERS: &[u64] = &[4, 5, 6];
 pub const DURATION: u32 = 169 * 60 * 60;
 
 pub fn init(sys: &System) -> Program {
-    USERS
-        .iter()
-        .for_each(|user| sys.mint_to(*user, 1_000_000_000));
+    init_with_config(sys, InitConfig::default(), create_config(2, 1_000_000_000))
+}
+
+pub fn init_with_config(sys: &System, config: InitConfig, create: CreateConfig) -> Program {
     let owner_user = USERS[0];
+    let auction_program = init_program(sys, config);
 
-    sys.init_logger();
+    init_nft(sys, owner_user);
+    let starting_price = create.starting_price;
+    let result = auction_program.send(owner_user, Action::Create(create));
+    println!("update_auction result = {:?}", reply(&result));
 
-    let auction_program = Program::current(sys);
+    let Ok(Event::AuctionStarted { lot, price, .. }) = reply(&result) else {
+        panic!("Unexpected reply: {:?}", reply(&result));
+    };
+    assert_eq!(lot.seller, owner_user.into());
+    assert_eq!(lot.token_id, 0.into());
+    assert_eq!(price, starting_price);
 
-    auction_program.send(owner_user, ());
+    auction_program
+}
 
-    init_nft(sys, owner_user);
-    let result = update_auction(&auction_program, owner_user, 2, 1_000_000_000);
-    println!(
-        "update_auction result = {:?}",
-        result.decoded_log::<Result<Event, Error>>()
-    );
+/// Initializes the auction program without starting an auction.
+pub fn init_program(sys: &System, config: InitConfig) -> Program {
+    USERS
+        .iter()
+        .for_each(|user| sys.mint_to(*user, 1_000_000_000));
 
-    assert!(result.contains(&(
-        owner_user,
-        Ok::<auction_io::auction::Event, Error>(Event::AuctionStarted {
-            token_owner: owner_user.into(),
-            price: 1_000_000_000,
-            token_id: 0.into(),
-        })
-        .encode()
-    )));
+    sys.init_logger();
 
+    let auction_program = Program::current(sys);
+    auction_program.send(USERS[0], config);
     auction_program
 }
 
@@ -103,6 +110,35 @@ pub fn init_nft(sys: &System, owner: u64) {
     assert!(res.contains(&log));
 }
 
+/// Mints the next NFT to `owner` and approves it to the auction program.
+#[allow(dead_code)]
+pub fn mint_approved(nft_program: &Program, owner: u64, token_id: u64, transaction_id: u64) {
+    let res = nft_program.send(
+        owner,
+        nft_io::NFTAction::Mint {
+            token_metadata: TokenMetadata {
+                name: "MyNFT".to_string(),
+                description: "NFTForAuction".to_string(),
+                media: "".to_string(),
+                reference: "".to_string(),
+            },
+            transaction_id,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = nft_program.send(
+        owner,
+        nft_io::NFTAction::Approve {
+            to: 1.into(),
+            token_id: token_id.into(),
+            transaction_id: transaction_id + 1,
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+#[allow(dead_code)]
 pub fn update_auction(
     auction: &Program,
     from: u64,
@@ -111,21 +147,69 @@ pub fn update_auction(
 ) -> RunResult {
     auction.send(
         from,
-        Action::Create(CreateConfig {
-            nft_contract_actor_id: nft_contract_id.into(),
-            starting_price,
-            discount_rate: 1_000,
-            token_id: 0.into(),
-            duration: Duration {
-                hours: 168,
-                minutes: 0,
-                seconds: 0,
-            },
-        }),
+        Action::Create(create_config(nft_contract_id, starting_price)),
     )
 }
 
+pub fn create_config(nft_contract_id: u64, starting_price: u128) -> CreateConfig {
+    CreateConfig {
+        nft_contract_actor_id: nft_contract_id.into(),
+        starting_price,
+        discount_rate: 1_000,
+        token_id: 0.into(),
+        duration: Duration {
+            hours: 168,
+            minutes: 0,
+            seconds: 0,
+            blocks: 0,
+        },
+        ..Default::default()
+    }
+}
+
 #[allow(dead_code)]
 pub fn nft_owner(nft_program: &Program, from: u64, token_id: TokenId) -> RunResult {
     nft_program.send(from, nft_io::NFTAction::Owner { token_id })
 }
+
+/// Decodes the auction reply from the messages of `result`.
+pub fn reply(result: &RunResult) -> Result<Event, Error> {
+    result
+        .log()
+        .iter()
+        .find_map(|log| Result::<VersionedEvent, Error>::decode(&mut log.payload()).ok())
+        .expect("No reply from the auction")
+        .map(|event| match event {
+            VersionedEvent::V2(event) => event,
+            VersionedEvent::V1(event) => panic!("Unexpected event layout: {event:?}"),
+        })
+}
+
+/// Sends `query` to the auction and returns its reply.
+#[allow(dead_code)]
+pub fn query(auction: &Program, query: StateQuery) -> StateReply {
+    match reply(&auction.send(USERS[0], Action::Query(query))) {
+        Ok(Event::StateReply(reply)) => reply,
+        result => panic!("Unexpected reply: {result:?}"),
+    }
+}
+
+/// Pages through the history like the `history*` metawasm functions.
+#[allow(dead_code)]
+pub fn history(
+    auction: &Program,
+    page: u32,
+    page_size: u32,
+    filter: HistoryFilter,
+) -> Vec<AuctionRecord> {
+    let AuctionState { history, .. } = auction.read_state().expect("Can't get state");
+    filter.page(&history, page, page_size)
+}
+
+#[allow(dead_code)]
+pub fn balance(auction: &Program, actor: u64) -> u128 {
+    let StateReply::Balance(balance) = query(auction, StateQuery::Balance(actor.into())) else {
+        panic!("Unexpected query reply");
+    };
+    balance
+}
@@ -11,19 +11,42 @@ fn is_not_active_after_time_is_over() {
     let auction = init(&sys);
     sys.spend_blocks(DURATION);
 
-    if let Ok(AuctionInfo { status, .. }) = auction.read_state() {
+    if let Ok(AuctionState {
+        info: AuctionInfo { status, .. },
+        ..
+    }) = auction.read_state()
+    {
         dbg!(&status);
         assert!(!matches!(status, Status::IsRunning))
     }
 }
 
+#[test]
+fn is_expired_after_time_is_over() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+
+    let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+    assert!(matches!(info.status, Status::Expired));
+    assert_eq!(
+        info.status,
+        Status::IsRunning.effective(info.expires_at, info.expires_at)
+    );
+}
+
 #[test]
 fn is_active_before_deal() {
     let sys = System::new();
 
     let auction = init(&sys);
 
-    if let Ok(AuctionInfo { status, .. }) = auction.read_state() {
+    if let Ok(AuctionState {
+        info: AuctionInfo { status, .. },
+        ..
+    }) = auction.read_state()
+    {
         dbg!(&status);
         assert!(matches!(status, Status::IsRunning));
     } else {
@@ -36,9 +59,19 @@ fn is_not_active_after_deal() {
     let sys = System::new();
 
     let auction = init(&sys);
-    auction.send_with_value(USERS[1], Action::Buy, 1_000_000_000);
+    auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
 
-    if let Ok(AuctionInfo { status, .. }) = auction.read_state() {
+    if let Ok(AuctionState {
+        info: AuctionInfo { status, .. },
+        ..
+    }) = auction.read_state()
+    {
         dbg!(&status);
         assert!(matches!(
             status,
@@ -50,3 +83,213 @@ fn is_not_active_after_deal() {
         panic!("Can't get state");
     }
 }
+
+#[test]
+fn sale_is_kept_in_history() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    auction.send_with_value(
+        USERS[1],
+        Action::Buy {
+            max_price: 1_000_000_000,
+        },
+        1_000_000_000,
+    );
+
+    let history = history(&auction, 0, 10, HistoryFilter::All);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].lot.seller, USERS[0].into());
+    assert_eq!(history[0].buyer, Some(USERS[1].into()));
+    assert_eq!(
+        history[0].outcome,
+        Outcome::Sold {
+            price: 1_000_000_000
+        }
+    );
+}
+
+#[test]
+fn relisted_auction_is_kept_in_history() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+    auction.send(USERS[0], Action::Relist(create_config(2, 800_000_000)));
+
+    let history = history(&auction, 0, 10, HistoryFilter::All);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].lot.auction_id, 1);
+    assert_eq!(history[0].buyer, None);
+    assert_eq!(history[0].outcome, Outcome::Expired);
+}
+
+#[test]
+fn history_is_paged() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    for _ in 0..3 {
+        sys.spend_blocks(DURATION);
+        auction.send(USERS[0], Action::Relist(create_config(2, 1_000_000_000)));
+    }
+
+    let page = history(&auction, 1, 2, HistoryFilter::All);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].lot.auction_id, 3);
+    assert!(history(&auction, 2, 2, HistoryFilter::All).is_empty());
+    assert!(history(&auction, u32::MAX, u32::MAX, HistoryFilter::All).is_empty());
+}
+
+#[test]
+fn history_is_bounded() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+    let config = CreateConfig {
+        duration: Duration {
+            minutes: 1,
+            ..Default::default()
+        },
+        ..create_config(2, 1_000_000_000)
+    };
+    for _ in 0..=MAX_HISTORY_RECORDS {
+        auction.send(USERS[0], Action::Relist(config.clone()));
+        sys.spend_blocks(60);
+    }
+
+    let history = history(&auction, 0, u32::MAX, HistoryFilter::All);
+    assert_eq!(history.len(), MAX_HISTORY_RECORDS);
+    assert_eq!(history[0].lot.auction_id, 2);
+}
+
+#[test]
+fn history_keeps_the_clock() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+    let config = CreateConfig {
+        duration: Duration {
+            blocks: 10,
+            ..Default::default()
+        },
+        decay_unit: DecayUnit::Block,
+        ..create_config(2, 1_000_000_000)
+    };
+    auction.send(USERS[0], Action::Relist(config));
+    sys.spend_blocks(10);
+    auction.send(USERS[0], Action::Relist(create_config(2, 1_000_000_000)));
+
+    let history = history(&auction, 0, u32::MAX, HistoryFilter::All);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].decay_unit, DecayUnit::Second);
+    assert_eq!(history[1].decay_unit, DecayUnit::Block);
+    assert!(history[1].ended_at - history[1].started_at >= 10);
+}
+
+#[test]
+fn history_is_filtered() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    sys.spend_blocks(DURATION);
+    auction.send(USERS[0], Action::Relist(create_config(2, 1_000_000_000)));
+
+    let seller = HistoryFilter::Seller(USERS[0].into());
+    assert_eq!(history(&auction, 0, 10, seller).len(), 1);
+    let other_seller = HistoryFilter::Seller(USERS[1].into());
+    assert!(history(&auction, 0, 10, other_seller).is_empty());
+
+    let collection = HistoryFilter::Collection(2.into());
+    assert_eq!(history(&auction, 0, 10, collection).len(), 1);
+    let other_collection = HistoryFilter::Collection(3.into());
+    assert!(history(&auction, 0, 10, other_collection).is_empty());
+}
+
+#[test]
+fn price_schedule_matches_current_price() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+    sys.spend_blocks(100_000);
+
+    let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+    let schedule = info.schedule();
+    assert_eq!(info.current_price, 900_000_000);
+    assert_eq!(
+        schedule.price_at(info.started_at + 100_000 * 1000),
+        info.current_price
+    );
+    assert_eq!(
+        schedule.reaches_price_at(900_000_000),
+        Some(info.started_at + 100_000 * 1000)
+    );
+    assert_eq!(
+        schedule.reaches_price_at(0),
+        Some(info.started_at + 1_000_000 * 1000)
+    );
+}
+
+#[test]
+fn price_schedule_points() {
+    let sys = System::new();
+
+    let auction = init(&sys);
+
+    let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+    let points = info
+        .schedule()
+        .points(info.started_at, info.started_at + 2_500, 1_000);
+    assert_eq!(
+        points,
+        vec![
+            (info.started_at, 1_000_000_000),
+            (info.started_at + 1_000, 999_999_000),
+            (info.started_at + 2_000, 999_998_000),
+            (info.started_at + 2_500, 999_998_000),
+        ]
+    );
+
+    let points = info.schedule().points(info.started_at, info.expires_at, 1);
+    assert_eq!(points.len() as u64, MAX_PRICE_POINTS);
+    assert_eq!(points.first(), Some(&(info.started_at, 1_000_000_000)));
+    assert_eq!(
+        points.last().map(|(timestamp, _)| *timestamp),
+        Some(info.expires_at)
+    );
+    assert!(points
+        .iter()
+        .all(|(timestamp, price)| info.schedule().price_at(*timestamp) == *price));
+}
+
+#[test]
+fn reverse_price_points_rise() {
+    let sys = System::new();
+
+    let auction = init_program(&sys, InitConfig::default());
+    auction.send_with_value(
+        USERS[1],
+        Action::CreateReverse(ReverseConfig {
+            starting_price: 100_000_000,
+            increase_rate: 1_000,
+            duration: Duration {
+                hours: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+        500_000_000,
+    );
+
+    let AuctionState { info, .. } = auction.read_state().expect("Can't get state");
+    assert_eq!(
+        info.price_points(info.started_at, info.started_at + 2_000, 1_000),
+        vec![
+            (info.started_at, 100_000_000),
+            (info.started_at + 1_000, 100_001_000),
+            (info.started_at + 2_000, 100_002_000),
+        ]
+    );
+}